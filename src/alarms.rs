@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{check_plant_access, value_as_bool, value_as_f64, Growatt, GrowattError, Result};
+
+/// How a metric is compared against an alarm's threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Below,
+    Above,
+}
+
+impl Comparison {
+    // The portal encodes the comparison as 0 (below) or 1 (above)
+    fn code(&self) -> &'static str {
+        match self {
+            Comparison::Below => "0",
+            Comparison::Above => "1",
+        }
+    }
+}
+
+/// A custom alert threshold configured for a plant in the Growatt app, e.g.
+/// "notify if daily energy is below 5 kWh".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmRule {
+    /// Server-side ID of the rule; `None` for a rule not saved yet.
+    pub id: Option<String>,
+    /// The monitored value as the portal names it, e.g. `"eToday"`.
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub enabled: bool,
+}
+
+impl AlarmRule {
+    // Rules without a metric, comparison or threshold are dropped
+    pub(crate) fn from_value(record: &serde_json::Value) -> Option<Self> {
+        let comparison = match record.get("compareType").and_then(value_as_f64)? as i64 {
+            0 => Comparison::Below,
+            1 => Comparison::Above,
+            _ => return None,
+        };
+
+        Some(Self {
+            id: record.get("id").and_then(|id| match id {
+                serde_json::Value::String(id) => Some(id.clone()),
+                serde_json::Value::Number(id) => Some(id.to_string()),
+                _ => None,
+            }),
+            metric: record.get("alarmType")?.as_str()?.to_string(),
+            comparison,
+            threshold: record.get("threshold").and_then(value_as_f64)?,
+            enabled: record.get("enable").and_then(value_as_bool).unwrap_or(false),
+        })
+    }
+}
+
+impl Growatt {
+    /// Lists the alert thresholds configured for a plant.
+    pub async fn get_plant_alarms(&mut self, plant_id: &str) -> Result<Vec<AlarmRule>> {
+        let form = [("plantId", plant_id)];
+
+        let json_response: serde_json::Value = self.post_form("/alarm/getPlantAlarmSetList", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+
+        match json_response.get("obj") {
+            // A plant without rules comes back as a null obj
+            Some(obj) if obj.is_null() => Ok(Vec::new()),
+            Some(obj) => Ok(obj
+                .get("datas")
+                .and_then(|datas| datas.as_array())
+                .map(|datas| datas.iter().filter_map(AlarmRule::from_value).collect())
+                .unwrap_or_default()),
+            None => Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            )),
+        }
+    }
+
+    /// Creates an alert threshold for a plant, or updates the rule with the
+    /// same `id`. Returns whether the server accepted it.
+    pub async fn set_plant_alarm(&mut self, plant_id: &str, rule: &AlarmRule) -> Result<bool> {
+        let threshold = rule.threshold.to_string();
+        let form = [
+            ("plantId", plant_id),
+            ("id", rule.id.as_deref().unwrap_or_default()),
+            ("alarmType", &rule.metric),
+            ("compareType", rule.comparison.code()),
+            ("threshold", &threshold),
+            ("enable", if rule.enabled { "1" } else { "0" }),
+        ];
+
+        let json_response: serde_json::Value = self.post_form("/alarm/savePlantAlarmSet", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+
+        json_response
+            .get("result")
+            .and_then(value_as_f64)
+            .map(|result| result == 1.0)
+            .ok_or_else(|| GrowattError::InvalidResponse("Invalid response structure".to_string()))
+    }
+}
@@ -0,0 +1,101 @@
+use std::future::Future;
+
+use crate::{
+    ChartDay, DeviceList, EnergyChart, Growatt, MixStatusData, MixTotalData, PlantData, PlantList, Result, SetCommand, Weather,
+};
+
+/// The core calls of [`Growatt`] as a trait, so code using a client can be
+/// written against `impl GrowattApi` and tested with a stand-in. Its futures
+/// are `Send`, so such code can run on a spawned task.
+///
+/// With the `mock` feature, `MockGrowattApi` implements it with
+/// [mockall](https://docs.rs/mockall) expectations. The methods mirror the
+/// inherent ones on [`Growatt`], which document them.
+#[cfg_attr(feature = "mock", mockall::automock)]
+pub trait GrowattApi {
+    fn login(&mut self, username: &str, password: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    fn logout(&mut self) -> impl Future<Output = Result<bool>> + Send;
+
+    fn is_logged_in(&self) -> bool;
+
+    fn get_plants(&mut self) -> impl Future<Output = Result<PlantList>> + Send;
+
+    fn get_plant(&mut self, plant_id: &str) -> impl Future<Output = Result<PlantData>> + Send;
+
+    fn get_devices(&mut self, plant_id: &str) -> impl Future<Output = Result<DeviceList>> + Send;
+
+    fn get_device_list(&mut self, plant_id: &str) -> impl Future<Output = Result<serde_json::Value>> + Send;
+
+    fn get_mix_ids(&mut self, plant_id: &str) -> impl Future<Output = Result<serde_json::Value>> + Send;
+
+    fn get_mix_total(&mut self, plant_id: &str, mix_sn: &str) -> impl Future<Output = Result<MixTotalData>> + Send;
+
+    fn get_mix_status(&mut self, plant_id: &str, mix_sn: &str) -> impl Future<Output = Result<serde_json::Value>> + Send;
+
+    fn get_mix_status_typed(&mut self, plant_id: &str, mix_sn: &str) -> impl Future<Output = Result<MixStatusData>> + Send;
+
+    fn get_energy_stats_daily_typed(&mut self, day: ChartDay, plant_id: &str, mix_sn: &str) -> impl Future<Output = Result<EnergyChart>> + Send;
+
+    fn get_plant_weather(&mut self, plant_id: &str) -> impl Future<Output = Result<Weather>> + Send;
+
+    fn set_param(&mut self, device_sn: &str, setting: &SetCommand) -> impl Future<Output = Result<bool>> + Send;
+}
+
+impl GrowattApi for Growatt {
+    async fn login(&mut self, username: &str, password: &str) -> Result<bool> {
+        Growatt::login(self, username, password).await
+    }
+
+    async fn logout(&mut self) -> Result<bool> {
+        Growatt::logout(self).await
+    }
+
+    fn is_logged_in(&self) -> bool {
+        Growatt::is_logged_in(self)
+    }
+
+    async fn get_plants(&mut self) -> Result<PlantList> {
+        Growatt::get_plants(self).await
+    }
+
+    async fn get_plant(&mut self, plant_id: &str) -> Result<PlantData> {
+        Growatt::get_plant(self, plant_id).await
+    }
+
+    async fn get_devices(&mut self, plant_id: &str) -> Result<DeviceList> {
+        Growatt::get_devices(self, plant_id).await
+    }
+
+    async fn get_device_list(&mut self, plant_id: &str) -> Result<serde_json::Value> {
+        Growatt::get_device_list(self, plant_id).await
+    }
+
+    async fn get_mix_ids(&mut self, plant_id: &str) -> Result<serde_json::Value> {
+        Growatt::get_mix_ids(self, plant_id).await
+    }
+
+    async fn get_mix_total(&mut self, plant_id: &str, mix_sn: &str) -> Result<MixTotalData> {
+        Growatt::get_mix_total(self, plant_id, mix_sn).await
+    }
+
+    async fn get_mix_status(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        Growatt::get_mix_status(self, plant_id, mix_sn).await
+    }
+
+    async fn get_mix_status_typed(&mut self, plant_id: &str, mix_sn: &str) -> Result<MixStatusData> {
+        Growatt::get_mix_status_typed(self, plant_id, mix_sn).await
+    }
+
+    async fn get_energy_stats_daily_typed(&mut self, day: ChartDay, plant_id: &str, mix_sn: &str) -> Result<EnergyChart> {
+        Growatt::get_energy_stats_daily_typed(self, day, plant_id, mix_sn).await
+    }
+
+    async fn get_plant_weather(&mut self, plant_id: &str) -> Result<Weather> {
+        Growatt::get_plant_weather(self, plant_id).await
+    }
+
+    async fn set_param(&mut self, device_sn: &str, setting: &SetCommand) -> Result<bool> {
+        Growatt::set_param(self, device_sn, setting).await
+    }
+}
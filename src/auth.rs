@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_bool, value_as_f64, Growatt, GrowattError, Result};
+
+/// The kind of account that logged in, from the login response's
+/// `accountType` (or `userType` on some server versions).
+///
+/// | Code | Role        |
+/// |------|-------------|
+/// | 0    | `EndUser`   |
+/// | 1    | `Installer` |
+/// | 2    | `Agent`     |
+/// | 3    | `Admin`     |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    EndUser,
+    Installer,
+    Agent,
+    Admin,
+    /// A code this crate doesn't know.
+    Unknown(i64),
+}
+
+impl From<i64> for Role {
+    fn from(code: i64) -> Self {
+        match code {
+            0 => Role::EndUser,
+            1 => Role::Installer,
+            2 => Role::Agent,
+            3 => Role::Admin,
+            other => Role::Unknown(other),
+        }
+    }
+}
+
+/// Details of a successful login.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginResult {
+    /// `None` when the response doesn't say.
+    pub role: Option<Role>,
+    pub user_id: Option<String>,
+    pub token: Option<String>,
+}
+
+impl LoginResult {
+    // Account details sit in a "user" object, or at the top level on older servers
+    pub(crate) fn from_value(json_response: &serde_json::Value) -> Self {
+        let user = json_response.get("user");
+        let field = |key: &str| user.and_then(|user| user.get(key)).or_else(|| json_response.get(key));
+
+        let role = ["accountType", "userType"]
+            .iter()
+            .find_map(|key| field(key).and_then(value_as_f64))
+            .map(|code| Role::from(code as i64));
+
+        let user_id = field("id").and_then(|id| match id {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        });
+
+        Self {
+            role,
+            user_id,
+            token: json_response.get("token").and_then(|v| v.as_str()).map(str::to_owned),
+        }
+    }
+}
+
+/// Login `result` codes for a wrong username or password. The app API
+/// reports 502 in `msg` instead.
+const LOGIN_RESULT_INVALID_CREDENTIALS: [i64; 2] = [-1, 502];
+/// Login `result` code for an account locked after too many failed attempts.
+const LOGIN_RESULT_ACCOUNT_LOCKED: i64 = -2;
+/// Login `result` code for an account frozen by Growatt or the installer.
+const LOGIN_RESULT_ACCOUNT_FROZEN: i64 = -3;
+/// Login code, sent in `msg`, for a username with no account behind it.
+const LOGIN_RESULT_ACCOUNT_NOT_FOUND: i64 = 501;
+
+/// Maps a rejected login response to its error.
+///
+/// A captcha request wins, flagged by the server or just mentioned in `msg`.
+/// Otherwise the `result` code decides, or a numeric `msg` where the server
+/// puts its code there instead:
+///
+/// | Code      | Error                                  |
+/// |-----------|----------------------------------------|
+/// | -1, 502   | [`GrowattError::InvalidCredentials`]   |
+/// | -2        | [`GrowattError::AccountLocked`]        |
+/// | -3        | [`GrowattError::AccountFrozen`]        |
+/// | 501       | [`GrowattError::AccountNotFound`]      |
+/// | any other | [`GrowattError::AuthError`] with `msg` |
+///
+/// A `result` of 0 with no code is a generic failure, unless `msg` says the
+/// account doesn't exist, which only catches accounts set to English.
+pub(crate) fn login_failure(json_response: &serde_json::Value) -> GrowattError {
+    let msg = json_response.get("msg").and_then(|v| v.as_str()).unwrap_or("Unknown error");
+
+    let flagged = ["needValidateCode", "isValidateCode", "validateCodeRequired"]
+        .iter()
+        .any(|key| json_response.get(*key).and_then(value_as_bool) == Some(true));
+    let mentioned = ["validatecode", "verification code", "captcha"]
+        .iter()
+        .any(|word| msg.to_lowercase().contains(word));
+    if flagged || mentioned {
+        let captcha = ["validateCodeUrl", "captchaUrl", "validateCodeImg", "captchaToken"]
+            .iter()
+            .find_map(|key| json_response.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_owned);
+        return GrowattError::CaptchaRequired { captcha };
+    }
+
+    let code = json_response
+        .get("result")
+        .and_then(value_as_f64)
+        .map(|code| code as i64)
+        .filter(|code| *code != 0)
+        .or_else(|| msg.trim().parse().ok());
+    match code {
+        Some(code) if LOGIN_RESULT_INVALID_CREDENTIALS.contains(&code) => GrowattError::InvalidCredentials,
+        Some(LOGIN_RESULT_ACCOUNT_LOCKED) => GrowattError::AccountLocked,
+        Some(LOGIN_RESULT_ACCOUNT_FROZEN) => GrowattError::AccountFrozen,
+        Some(LOGIN_RESULT_ACCOUNT_NOT_FOUND) => GrowattError::AccountNotFound,
+        None if msg.to_lowercase().contains("not exist") => GrowattError::AccountNotFound,
+        _ => GrowattError::AuthError(msg.to_string()),
+    }
+}
+
+/// A username and password, for [`Growatt::with_credentials`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+// Keeps passwords out of logs
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Growatt {
+    /// Registers several accounts that can see the same plants, in order of
+    /// preference. Automatic logins try them in turn until one succeeds, so a
+    /// locked-out primary account falls back to the next one.
+    pub fn with_credentials(mut self, credentials: Vec<Credentials>) -> Self {
+        if let Some(primary) = credentials.first() {
+            self.username = Some(primary.username.clone());
+            self.password = Some(primary.password.clone());
+        }
+        self.credentials = credentials;
+        self
+    }
+
+    /// The username of the account currently logged in, or about to be used.
+    pub fn active_username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    /// Logs in with the credentials registered with
+    /// [`Growatt::with_credentials`], starting from the primary account.
+    ///
+    /// Only rejected logins (a [`GrowattError::AuthError`],
+    /// [`GrowattError::InvalidCredentials`], [`GrowattError::AccountLocked`],
+    /// [`GrowattError::AccountFrozen`], [`GrowattError::AccountNotFound`] or
+    /// [`GrowattError::CaptchaRequired`])
+    /// move on to the next account; a network error is
+    /// returned right away, as the others would fail the same way. If every
+    /// account is rejected, the last rejection is returned.
+    pub async fn login_with_failover(&mut self) -> Result<LoginResult> {
+        let mut last_error = GrowattError::NotLoggedIn;
+        for credentials in self.credentials.clone() {
+            match self.login_detailed(&credentials.username, &credentials.password).await {
+                Ok(login_result) => return Ok(login_result),
+                Err(
+                    e @ (GrowattError::AuthError(_)
+                    | GrowattError::InvalidCredentials
+                    | GrowattError::AccountLocked
+                    | GrowattError::AccountFrozen
+                    | GrowattError::AccountNotFound
+                    | GrowattError::CaptchaRequired { .. }),
+                ) => last_error = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error)
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_f64, Growatt, GrowattError, Result};
+
+/// One cell of a battery module.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CellData {
+    pub voltage_mv: Option<f64>,
+    pub temp_c: Option<f64>,
+}
+
+/// Cell-level readings of a battery pack, with pack-level extremes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatteryCells {
+    pub cells: Vec<CellData>,
+    pub min_voltage_mv: Option<f64>,
+    pub max_voltage_mv: Option<f64>,
+    /// Spread between the highest and lowest cell voltage; a growing delta
+    /// is an early sign of a failing cell.
+    pub delta_mv: Option<f64>,
+    pub min_temp_c: Option<f64>,
+    pub max_temp_c: Option<f64>,
+}
+
+impl BatteryCells {
+    // Cells are reported as numbered keys, cellVoltage1 / cellTemp1 onwards.
+    // Returns None when the battery reports no cells at all.
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Option<Self> {
+        let cells: Vec<CellData> = (1..)
+            .map(|index| CellData {
+                voltage_mv: obj.get(format!("cellVoltage{}", index)).and_then(value_as_f64),
+                temp_c: obj.get(format!("cellTemp{}", index)).and_then(value_as_f64),
+            })
+            .take_while(|cell| cell.voltage_mv.is_some() || cell.temp_c.is_some())
+            .collect();
+
+        if cells.is_empty() {
+            return None;
+        }
+
+        let min_max = |values: Vec<f64>| {
+            let min = values.iter().copied().reduce(f64::min);
+            let max = values.iter().copied().reduce(f64::max);
+            (min, max)
+        };
+        let (min_voltage_mv, max_voltage_mv) = min_max(cells.iter().filter_map(|cell| cell.voltage_mv).collect());
+        let (min_temp_c, max_temp_c) = min_max(cells.iter().filter_map(|cell| cell.temp_c).collect());
+
+        Some(Self {
+            delta_mv: max_voltage_mv.zip(min_voltage_mv).map(|(max, min)| max - min),
+            cells,
+            min_voltage_mv,
+            max_voltage_mv,
+            min_temp_c,
+            max_temp_c,
+        })
+    }
+}
+
+impl Growatt {
+    /// Fetches per-cell voltages and temperatures of a BMS-integrated battery.
+    ///
+    /// Returns [`GrowattError::NoData`] for batteries that don't report cell
+    /// data.
+    pub async fn get_battery_cells(&mut self, plant_id: &str, battery_sn: &str) -> Result<BatteryCells> {
+        let obj = self
+            .post_plant_form("/panel/battery/getBatteryCellData", plant_id, &[("batterySn", battery_sn)])
+            .await?;
+        BatteryCells::from_value(&obj).ok_or(GrowattError::NoData)
+    }
+}
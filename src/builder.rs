@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use crate::rate_limit::MIN_REQUESTS_PER_SECOND;
+use crate::{parse_proxy, Credentials, Growatt, GrowattError, Result};
+
+/// Collects a client's configuration and validates it all at once in
+/// [`GrowattBuilder::build`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use growatt::GrowattBuilder;
+///
+/// # fn main() -> growatt::Result<()> {
+/// let client = GrowattBuilder::default()
+///     .credentials("username", "password")
+///     .timeout(Duration::from_secs(10))
+///     .retry(3, Duration::from_millis(500))
+///     .rate_limit(2.0)
+///     .proxy("http://proxy.corp:3128")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Settings left out keep the defaults of [`Growatt::new`]. Options without
+/// a builder setter, such as hooks, are still available as `with_*` methods
+/// on the built client.
+#[derive(Debug, Clone, Default)]
+pub struct GrowattBuilder {
+    base_url: Option<String>,
+    credentials: Vec<Credentials>,
+    api_token: Option<String>,
+    timeout: Option<Duration>,
+    session_duration: Option<Duration>,
+    online_threshold: Option<Duration>,
+    retry: Option<(u32, Duration)>,
+    retry_budget: Option<f64>,
+    rate_limit: Option<f64>,
+    circuit_breaker: Option<(u32, Duration)>,
+    proxy: Option<String>,
+    proxy_auth: Option<(String, String)>,
+    user_agent: Option<String>,
+    max_response_size: Option<usize>,
+    max_pages: Option<usize>,
+    plant_concurrency: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+    http2_keep_alive_interval: Option<Duration>,
+}
+
+impl GrowattBuilder {
+    /// See [`Growatt::with_base_url`].
+    pub fn base_url(&mut self, base_url: &str) -> &mut Self {
+        self.base_url = Some(base_url.to_string());
+        self
+    }
+
+    /// Stores credentials for automatic logins. Called more than once, the
+    /// later accounts become fallbacks, see [`Growatt::with_credentials`].
+    pub fn credentials(&mut self, username: &str, password: &str) -> &mut Self {
+        self.credentials.push(Credentials::new(username, password));
+        self
+    }
+
+    /// See [`Growatt::with_api_token`].
+    pub fn api_token(&mut self, token: &str) -> &mut Self {
+        self.api_token = Some(token.to_string());
+        self
+    }
+
+    /// See [`Growatt::with_timeout`].
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How long a login is trusted before logging in again.
+    pub fn session_duration(&mut self, duration: Duration) -> &mut Self {
+        self.session_duration = Some(duration);
+        self
+    }
+
+    /// See [`Growatt::with_online_threshold`].
+    pub fn online_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.online_threshold = Some(threshold);
+        self
+    }
+
+    /// See [`Growatt::with_retry`].
+    pub fn retry(&mut self, max_attempts: u32, base_delay: Duration) -> &mut Self {
+        self.retry = Some((max_attempts, base_delay));
+        self
+    }
+
+    /// See [`Growatt::with_retry_budget`].
+    pub fn retry_budget(&mut self, ratio: f64) -> &mut Self {
+        self.retry_budget = Some(ratio);
+        self
+    }
+
+    /// See [`Growatt::with_rate_limit`].
+    pub fn rate_limit(&mut self, requests_per_second: f64) -> &mut Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// See [`Growatt::with_circuit_breaker`].
+    pub fn circuit_breaker(&mut self, failure_threshold: u32, cooldown: Duration) -> &mut Self {
+        self.circuit_breaker = Some((failure_threshold, cooldown));
+        self
+    }
+
+    /// See [`Growatt::with_proxy`].
+    pub fn proxy(&mut self, url: &str) -> &mut Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Credentials for the proxy set with [`GrowattBuilder::proxy`].
+    pub fn proxy_auth(&mut self, username: &str, password: &str) -> &mut Self {
+        self.proxy_auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// See [`Growatt::with_user_agent`].
+    pub fn user_agent(&mut self, user_agent: &str) -> &mut Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// See [`Growatt::with_max_response_size`].
+    pub fn max_response_size(&mut self, bytes: usize) -> &mut Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// See [`Growatt::with_max_pages`].
+    pub fn max_pages(&mut self, pages: usize) -> &mut Self {
+        self.max_pages = Some(pages);
+        self
+    }
+
+    /// See [`Growatt::with_plant_concurrency`].
+    pub fn plant_concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.plant_concurrency = Some(concurrency);
+        self
+    }
+
+    /// See [`Growatt::with_tcp_keepalive`].
+    pub fn tcp_keepalive(&mut self, interval: Duration) -> &mut Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// See [`Growatt::with_http2_keep_alive_interval`].
+    pub fn http2_keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Validates the configuration and builds the client. Fails with
+    /// [`GrowattError::InvalidConfig`] naming the first invalid setting.
+    pub fn build(&self) -> Result<Growatt> {
+        self.validate()?;
+
+        let mut client = Growatt::unconfigured();
+
+        if let Some(base_url) = &self.base_url {
+            client = client.with_base_url(base_url);
+        }
+        if !self.credentials.is_empty() {
+            client = client.with_credentials(self.credentials.clone());
+        }
+        if let Some(token) = &self.api_token {
+            client = client.with_api_token(token);
+        }
+        if let Some(timeout) = self.timeout {
+            client = client.with_timeout(timeout);
+        }
+        if let Some(duration) = self.session_duration {
+            client.session_duration = to_chrono(duration, "session_duration")?;
+        }
+        if let Some(threshold) = self.online_threshold {
+            client.online_threshold = to_chrono(threshold, "online_threshold")?;
+        }
+        if let Some((max_attempts, base_delay)) = self.retry {
+            client = client.with_retry(max_attempts, base_delay);
+        }
+        if let Some(ratio) = self.retry_budget {
+            client = client.with_retry_budget(ratio);
+        }
+        if let Some(requests_per_second) = self.rate_limit {
+            client = client.with_rate_limit(requests_per_second);
+        }
+        if let Some((failure_threshold, cooldown)) = self.circuit_breaker {
+            client = client.with_circuit_breaker(failure_threshold, cooldown);
+        }
+        if let Some(bytes) = self.max_response_size {
+            client = client.with_max_response_size(bytes);
+        }
+        if let Some(pages) = self.max_pages {
+            client = client.with_max_pages(pages);
+        }
+        if let Some(concurrency) = self.plant_concurrency {
+            client = client.with_plant_concurrency(concurrency);
+        }
+
+        // Connection settings each rebuild the HTTP client, so they are set
+        // on the options first and applied with a single rebuild
+        if let Some(user_agent) = &self.user_agent {
+            client.connection.user_agent = Some(
+                reqwest::header::HeaderValue::from_str(user_agent)
+                    .map_err(|_| GrowattError::InvalidConfig(format!("invalid user agent {:?}", user_agent)))?,
+            );
+        }
+        if let Some(url) = &self.proxy {
+            let mut proxy = parse_proxy(url)?;
+            if let Some((username, password)) = &self.proxy_auth {
+                proxy = proxy.basic_auth(username, password);
+            }
+            client.connection.proxy = Some(proxy);
+        }
+        client.connection.tcp_keepalive = self.tcp_keepalive;
+        client.connection.http2_keep_alive_interval = self.http2_keep_alive_interval;
+
+        Ok(client.rebuild_client())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(GrowattError::InvalidConfig(message.to_string()));
+
+        if let Some(base_url) = &self.base_url {
+            if reqwest::Url::parse(base_url).is_err() {
+                return Err(GrowattError::InvalidConfig(format!("invalid base URL {:?}", base_url)));
+            }
+        }
+        if self.credentials.iter().any(|credentials| credentials.username.is_empty()) {
+            return invalid("username must not be empty");
+        }
+        if self.timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return invalid("timeout must be non-zero");
+        }
+        if self.session_duration.is_some_and(|duration| duration.is_zero()) {
+            return invalid("session_duration must be non-zero");
+        }
+        if self.retry.is_some_and(|(max_attempts, _)| max_attempts == 0) {
+            return invalid("retry needs at least one attempt");
+        }
+        if self.retry_budget.is_some_and(|ratio| !ratio.is_finite() || ratio < 0.0) {
+            return invalid("retry_budget must be a non-negative number");
+        }
+        if self.rate_limit.is_some_and(|rate| !rate.is_finite() || rate < MIN_REQUESTS_PER_SECOND) {
+            return invalid("rate_limit must be at least one request a day");
+        }
+        if let Some((failure_threshold, cooldown)) = self.circuit_breaker {
+            if failure_threshold == 0 || cooldown.is_zero() {
+                return invalid("circuit_breaker needs a non-zero threshold and cooldown");
+            }
+        }
+        if self.proxy_auth.is_some() && self.proxy.is_none() {
+            return invalid("proxy_auth needs a proxy");
+        }
+        if self.max_response_size == Some(0) {
+            return invalid("max_response_size must be non-zero");
+        }
+        if self.max_pages == Some(0) {
+            return invalid("max_pages must be non-zero");
+        }
+        if self.plant_concurrency == Some(0) {
+            return invalid("plant_concurrency must be non-zero");
+        }
+        Ok(())
+    }
+}
+
+fn to_chrono(duration: Duration, name: &str) -> Result<chrono::Duration> {
+    chrono::Duration::from_std(duration).map_err(|_| GrowattError::InvalidConfig(format!("{} is too long", name)))
+}
+
+impl Growatt {
+    /// Starts a [`GrowattBuilder`].
+    pub fn builder() -> GrowattBuilder {
+        GrowattBuilder::default()
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::PlantData;
+
+/// Per-plant cache for [`Growatt::get_plant`](crate::Growatt::get_plant) with
+/// stale-while-revalidate semantics.
+pub(crate) struct PlantDataCache {
+    ttl: Duration,
+    stale_grace: Duration,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+struct CacheEntry {
+    data: PlantData,
+    fetched_at: Instant,
+    refreshing: bool,
+}
+
+pub(crate) enum Lookup {
+    /// Within the TTL: serve as is.
+    Fresh(PlantData),
+    /// Past the TTL but within the grace period: serve, and refresh in the
+    /// background if `refresh` is set (i.e. no refresh is running yet).
+    Stale { data: PlantData, refresh: bool },
+    /// Not cached, or too old to serve.
+    Miss,
+}
+
+impl PlantDataCache {
+    pub(crate) fn new(ttl: Duration, stale_grace: Duration) -> Self {
+        Self {
+            ttl,
+            stale_grace,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn lookup(&self, plant_id: &str) -> Lookup {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(plant_id) else {
+            return Lookup::Miss;
+        };
+
+        let age = entry.fetched_at.elapsed();
+        if age <= self.ttl {
+            Lookup::Fresh(entry.data.clone())
+        } else if age <= self.ttl + self.stale_grace {
+            let refresh = !entry.refreshing;
+            entry.refreshing = true;
+            Lookup::Stale { data: entry.data.clone(), refresh }
+        } else {
+            Lookup::Miss
+        }
+    }
+
+    pub(crate) fn store(&self, plant_id: &str, data: PlantData) {
+        self.entries.lock().unwrap().insert(
+            plant_id.to_string(),
+            CacheEntry { data, fetched_at: Instant::now(), refreshing: false },
+        );
+    }
+
+    // Lets the next stale lookup try again after a failed background refresh
+    pub(crate) fn refresh_failed(&self, plant_id: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(plant_id) {
+            entry.refreshing = false;
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Months, NaiveDate, NaiveTime};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{check_plant_access, value_as_f64, Growatt, GrowattError, Result};
+
+/// How many day charts are fetched at once for a date range.
+const DAY_CHART_CONCURRENCY: usize = 4;
+
+/// The period an energy chart request is anchored to.
+///
+/// The chart endpoints are picky about date formatting: the day chart wants a
+/// full date, the month chart a `YYYY-MM` month and the year/total charts a
+/// bare four-digit year. A wrongly formatted anchor yields an empty response
+/// rather than an error, so the formatting lives here instead of in callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Year(i32),
+    Month(NaiveDate),
+    Day(NaiveDate),
+}
+
+impl Anchor {
+    /// Formats the anchor the way the chart endpoints expect it.
+    pub fn to_param(&self) -> String {
+        match self {
+            Anchor::Year(year) => format!("{:04}", year),
+            Anchor::Month(date) => date.format("%Y-%m").to_string(),
+            Anchor::Day(date) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// A day anchor, accepted by the daily chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartDay(pub NaiveDate);
+
+/// A month anchor, accepted by the monthly chart. The day of the date is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartMonth(pub NaiveDate);
+
+/// A year anchor, accepted by the yearly and total charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartYear(pub i32);
+
+impl From<ChartDay> for Anchor {
+    fn from(day: ChartDay) -> Self {
+        Anchor::Day(day.0)
+    }
+}
+
+impl From<ChartMonth> for Anchor {
+    fn from(month: ChartMonth) -> Self {
+        Anchor::Month(month.0)
+    }
+}
+
+impl From<ChartYear> for Anchor {
+    fn from(year: ChartYear) -> Self {
+        Anchor::Year(year.0)
+    }
+}
+
+/// One point of a day's power curve. Points the device didn't report are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PowerSample {
+    pub time: NaiveTime,
+    /// AC output power (W).
+    pub pac: Option<f64>,
+    /// PV input power (W).
+    pub ppv: Option<f64>,
+}
+
+/// The series of a MIX energy chart, as returned by the four
+/// `get_energy_stats_*` endpoints.
+///
+/// Each series has one entry per point of the chart: per five minutes for the
+/// day chart, per day for the month chart and per month or year for the
+/// others. Points the portal sent as `null` (no data yet, or the device was
+/// offline) are `None`, never 0. Series the portal left out are `None` as a
+/// whole.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnergyChart {
+    /// PV generation.
+    pub ppv: Option<Vec<Option<f64>>>,
+    /// Output of the system to loads and grid (`sysOut`).
+    pub sys_out: Option<Vec<Option<f64>>>,
+    /// Local load consumption (`userLoad`).
+    pub user_load: Option<Vec<Option<f64>>>,
+    /// Imported from the grid (`pacToUser`).
+    pub pac_to_user: Option<Vec<Option<f64>>>,
+    /// Exported to the grid (`pacToGrid`).
+    pub pac_to_grid: Option<Vec<Option<f64>>>,
+    /// Any other series the chart carried, by the portal's name.
+    pub other: BTreeMap<String, Vec<Option<f64>>>,
+    /// Battery charge energy over the charted period (kWh), when reported.
+    pub e_charge: Option<f64>,
+}
+
+impl EnergyChart {
+    /// Reads a chart response. Fails if it has no `obj.charts`.
+    pub fn from_response(json_response: &serde_json::Value) -> Result<Self> {
+        let obj = json_response.get("obj");
+        let charts = obj
+            .and_then(|obj| obj.get("charts"))
+            .and_then(|charts| charts.as_object())
+            .ok_or_else(|| GrowattError::InvalidResponse("Invalid response structure".to_string()))?;
+
+        let mut chart = EnergyChart {
+            e_charge: obj.and_then(|obj| obj.get("eCharge")).and_then(value_as_f64),
+            ..Default::default()
+        };
+        for (name, values) in charts {
+            let Some(values) = values.as_array() else {
+                continue;
+            };
+            let series: Vec<Option<f64>> = values.iter().map(value_as_f64).collect();
+            match name.as_str() {
+                "ppv" => chart.ppv = Some(series),
+                "sysOut" => chart.sys_out = Some(series),
+                "userLoad" => chart.user_load = Some(series),
+                "pacToUser" => chart.pac_to_user = Some(series),
+                "pacToGrid" => chart.pac_to_grid = Some(series),
+                _ => {
+                    chart.other.insert(name.clone(), series);
+                }
+            }
+        }
+
+        Ok(chart)
+    }
+
+    /// Whether the chart has no data point at all, as for a day before the
+    /// inverter was installed.
+    pub fn is_empty(&self) -> bool {
+        self.series().iter().all(|(_, values)| values.iter().all(Option::is_none))
+    }
+
+    // Every series the chart carries, by the portal's name
+    pub(crate) fn series(&self) -> Vec<(&str, &[Option<f64>])> {
+        let known = [
+            ("ppv", &self.ppv),
+            ("sysOut", &self.sys_out),
+            ("userLoad", &self.user_load),
+            ("pacToUser", &self.pac_to_user),
+            ("pacToGrid", &self.pac_to_grid),
+        ];
+        known
+            .into_iter()
+            .filter_map(|(name, values)| Some((name, values.as_deref()?)))
+            .chain(self.other.iter().map(|(name, values)| (name.as_str(), values.as_slice())))
+            .collect()
+    }
+
+    // The series of that name, added empty if the chart doesn't carry it
+    fn series_mut(&mut self, name: &str) -> &mut Vec<Option<f64>> {
+        match name {
+            "ppv" => self.ppv.get_or_insert_with(Vec::new),
+            "sysOut" => self.sys_out.get_or_insert_with(Vec::new),
+            "userLoad" => self.user_load.get_or_insert_with(Vec::new),
+            "pacToUser" => self.pac_to_user.get_or_insert_with(Vec::new),
+            "pacToGrid" => self.pac_to_grid.get_or_insert_with(Vec::new),
+            _ => self.other.entry(name.to_string()).or_default(),
+        }
+    }
+}
+
+/// Converts a full chart response, see [`EnergyChart::from_response`].
+impl TryFrom<&serde_json::Value> for EnergyChart {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        EnergyChart::from_response(value)
+    }
+}
+
+/// How missing days are filled in a daily series, e.g. days an inverter was
+/// offline. For a range of day charts, each point of each series is filled
+/// across the days the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapFill {
+    /// Return the series as received, gaps included.
+    #[default]
+    None,
+    /// Missing days are 0.
+    Zero,
+    /// Missing days are interpolated linearly between the neighbouring known
+    /// days. Days before the first or after the last known day take its value.
+    Interpolate,
+    /// Missing days repeat the last known value. Days before the first known
+    /// day are left out, as there is nothing to carry.
+    CarryForward,
+}
+
+impl GapFill {
+    /// Fills the gaps in `series` so that it has one entry per day from
+    /// `start` to `end` (inclusive). Entries outside the range are dropped.
+    pub fn apply(&self, series: &[(NaiveDate, f64)], start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, f64)> {
+        let mut known: Vec<(NaiveDate, f64)> = series
+            .iter()
+            .copied()
+            .filter(|(date, _)| (start..=end).contains(date))
+            .collect();
+        known.sort_by_key(|(date, _)| *date);
+        known.dedup_by_key(|(date, _)| *date);
+
+        if *self == GapFill::None || (known.is_empty() && *self != GapFill::Zero) {
+            return known;
+        }
+
+        let mut filled = Vec::new();
+        let mut next = 0;
+        let mut previous: Option<(NaiveDate, f64)> = None;
+        for date in start.iter_days().take_while(|date| *date <= end) {
+            if known.get(next).is_some_and(|(known_date, _)| *known_date == date) {
+                previous = Some(known[next]);
+                filled.push(known[next]);
+                next += 1;
+                continue;
+            }
+
+            let value = match self {
+                GapFill::Zero => Some(0.0),
+                GapFill::CarryForward => previous.map(|(_, value)| value),
+                GapFill::Interpolate => match (previous, known.get(next)) {
+                    (Some((from_date, from)), Some(&(to_date, to))) => {
+                        let span = (to_date - from_date).num_days() as f64;
+                        let offset = (date - from_date).num_days() as f64;
+                        Some(from + (to - from) * offset / span)
+                    }
+                    (Some((_, value)), None) | (None, Some(&(_, value))) => Some(value),
+                    (None, None) => None,
+                },
+                GapFill::None => None,
+            };
+            if let Some(value) = value {
+                filled.push((date, value));
+            }
+        }
+
+        filled
+    }
+
+    // Fills the days missing from a range of day charts point by point: each
+    // point of each series is filled across the days as a daily series would
+    // be. Charts of the days that have one are kept as they are.
+    pub(crate) fn apply_charts(
+        &self,
+        charts: Vec<(NaiveDate, EnergyChart)>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(NaiveDate, EnergyChart)> {
+        if *self == GapFill::None {
+            return charts;
+        }
+
+        let known: BTreeMap<NaiveDate, EnergyChart> = charts.into_iter().collect();
+        let mut shape: BTreeMap<&str, usize> = BTreeMap::new();
+        for chart in known.values() {
+            for (name, values) in chart.series() {
+                let len = shape.entry(name).or_default();
+                *len = (*len).max(values.len());
+            }
+        }
+
+        let mut filled: BTreeMap<NaiveDate, EnergyChart> = BTreeMap::new();
+        for (name, len) in shape {
+            for point in 0..len {
+                let series: Vec<(NaiveDate, f64)> = known
+                    .iter()
+                    .filter_map(|(date, chart)| {
+                        let (_, values) = chart.series().into_iter().find(|(series, _)| *series == name)?;
+                        Some((*date, (*values.get(point)?)?))
+                    })
+                    .collect();
+                for (date, value) in self.apply(&series, start, end) {
+                    if known.contains_key(&date) {
+                        continue;
+                    }
+                    let values = filled.entry(date).or_default().series_mut(name);
+                    values.resize(len, None);
+                    values[point] = Some(value);
+                }
+            }
+        }
+
+        let e_charge: Vec<(NaiveDate, f64)> = known
+            .iter()
+            .filter_map(|(date, chart)| Some((*date, chart.e_charge?)))
+            .collect();
+        for (date, value) in self.apply(&e_charge, start, end) {
+            if let Some(chart) = filled.get_mut(&date) {
+                chart.e_charge = Some(value);
+            }
+        }
+
+        let mut charts = known;
+        charts.extend(filled);
+        charts.into_iter().collect()
+    }
+}
+
+impl Growatt {
+    /// Fetches a MIX inverter's daily PV energy (kWh) between two dates
+    /// (inclusive), month chart by month chart, with gaps filled per `fill`.
+    /// An inverted range fails with [`GrowattError::InvalidInput`].
+    pub async fn get_daily_pv_energy_range(
+        &mut self,
+        plant_id: &str,
+        mix_sn: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        fill: GapFill,
+    ) -> Result<Vec<(NaiveDate, f64)>> {
+        if start > end {
+            return Err(GrowattError::InvalidInput(format!(
+                "Start date {} is after end date {}",
+                start, end
+            )));
+        }
+
+        let mut series = Vec::new();
+        let mut month = start.with_day(1).unwrap_or(start);
+        while month <= end {
+            let date = Anchor::Month(month).to_param();
+            let chart = self
+                .post_chart_form("/panel/mix/getMIXEnergyMonthChart", ("date", &date), plant_id, mix_sn)
+                .await?;
+            series.extend(month_chart_series(&chart, month));
+            month = match month.checked_add_months(Months::new(1)) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        Ok(fill.apply(&series, start, end))
+    }
+
+    /// Fetches a MIX inverter's day charts between two dates (inclusive),
+    /// several days at a time, in date order.
+    ///
+    /// Days without any data are left out. Any other failure fails the whole
+    /// range, as a report with silently missing days would be misleading. An
+    /// inverted range fails with [`GrowattError::InvalidInput`].
+    pub async fn get_energy_range_daily(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        plant_id: &str,
+        mix_sn: &str,
+    ) -> Result<Vec<(NaiveDate, EnergyChart)>> {
+        self.get_energy_range_daily_filled(from, to, plant_id, mix_sn, GapFill::None)
+            .await
+    }
+
+    /// Fetches day charts like [`Growatt::get_energy_range_daily`], with days
+    /// without any data filled per `fill` instead of left out.
+    pub async fn get_energy_range_daily_filled(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        plant_id: &str,
+        mix_sn: &str,
+        fill: GapFill,
+    ) -> Result<Vec<(NaiveDate, EnergyChart)>> {
+        if from > to {
+            return Err(GrowattError::InvalidInput(format!(
+                "Start date {} is after end date {}",
+                from, to
+            )));
+        }
+
+        self.check_login().await?;
+
+        let this = &*self;
+        let days: Vec<(NaiveDate, Result<Option<EnergyChart>>)> = stream::iter(from.iter_days().take_while(|day| *day <= to))
+            .map(|day| async move { (day, this.fetch_day_chart(day, plant_id, mix_sn).await) })
+            .buffered(DAY_CHART_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut charts = Vec::new();
+        for (day, chart) in days {
+            if let Some(chart) = chart? {
+                charts.push((day, chart));
+            }
+        }
+        Ok(fill.apply_charts(charts, from, to))
+    }
+
+    // None for a day the portal has no data for, which comes back without
+    // charts or with only nulls
+    async fn fetch_day_chart(&self, day: NaiveDate, plant_id: &str, mix_sn: &str) -> Result<Option<EnergyChart>> {
+        let date = Anchor::Day(day).to_param();
+        let form = [("date", date.as_str()), ("plantId", plant_id), ("mixSn", mix_sn)];
+        let json_response = self.fetch_form("/panel/mix/getMIXEnergyDayChart", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+
+        if json_response.get("obj").and_then(|obj| obj.get("charts")).is_none() {
+            return Ok(None);
+        }
+        let chart = EnergyChart::from_response(&json_response)?;
+        Ok((!chart.is_empty()).then_some(chart))
+    }
+
+    /// Fetches a MIX inverter's power curve for a day, from the same chart as
+    /// [`Growatt::get_energy_stats_daily_typed`].
+    pub async fn get_day_power_curve(&mut self, plant_id: &str, mix_sn: &str, date: NaiveDate) -> Result<Vec<PowerSample>> {
+        let date = Anchor::Day(date).to_param();
+        let chart = self
+            .post_chart_form("/panel/mix/getMIXEnergyDayChart", ("date", &date), plant_id, mix_sn)
+            .await?;
+        Ok(day_power_curve(&chart))
+    }
+
+    /// Fetches a MIX inverter's day chart as an [`EnergyChart`].
+    pub async fn get_energy_stats_daily_typed(&mut self, day: ChartDay, plant_id: &str, mix_sn: &str) -> Result<EnergyChart> {
+        let date = Anchor::from(day).to_param();
+        let json_response = self
+            .post_chart_form("/panel/mix/getMIXEnergyDayChart", ("date", &date), plant_id, mix_sn)
+            .await?;
+        EnergyChart::from_response(&json_response)
+    }
+
+    /// Fetches a MIX inverter's month chart as an [`EnergyChart`].
+    pub async fn get_energy_stats_monthly_typed(&mut self, month: ChartMonth, plant_id: &str, mix_sn: &str) -> Result<EnergyChart> {
+        let date = Anchor::from(month).to_param();
+        let json_response = self
+            .post_chart_form("/panel/mix/getMIXEnergyMonthChart", ("date", &date), plant_id, mix_sn)
+            .await?;
+        EnergyChart::from_response(&json_response)
+    }
+
+    /// Fetches a MIX inverter's year chart as an [`EnergyChart`].
+    pub async fn get_energy_stats_yearly_typed(&mut self, year: ChartYear, plant_id: &str, mix_sn: &str) -> Result<EnergyChart> {
+        let year = Anchor::from(year).to_param();
+        let json_response = self
+            .post_chart_form("/panel/mix/getMIXEnergyYearChart", ("year", &year), plant_id, mix_sn)
+            .await?;
+        EnergyChart::from_response(&json_response)
+    }
+
+    /// Fetches a MIX inverter's total chart as an [`EnergyChart`].
+    pub async fn get_energy_stats_total_typed(&mut self, year: ChartYear, plant_id: &str, mix_sn: &str) -> Result<EnergyChart> {
+        let year = Anchor::from(year).to_param();
+        let json_response = self
+            .post_chart_form("/panel/mix/getMIXEnergyTotalChart", ("year", &year), plant_id, mix_sn)
+            .await?;
+        EnergyChart::from_response(&json_response)
+    }
+}
+
+// The month chart lists one PV value per day of the month; days without data
+// are null
+pub(crate) fn month_chart_series(chart: &serde_json::Value, month: NaiveDate) -> Vec<(NaiveDate, f64)> {
+    chart
+        .get("obj")
+        .and_then(|obj| obj.get("charts"))
+        .and_then(|charts| charts.get("ppv"))
+        .and_then(|ppv| ppv.as_array())
+        .map(|days| {
+            days.iter()
+                .enumerate()
+                .filter_map(|(index, value)| Some((month.with_day(index as u32 + 1)?, value_as_f64(value)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The day chart spreads its points evenly over the day, usually one every
+// five minutes (288 points)
+pub(crate) fn day_power_curve(chart: &serde_json::Value) -> Vec<PowerSample> {
+    let Some(charts) = chart.get("obj").and_then(|obj| obj.get("charts")) else {
+        return Vec::new();
+    };
+    let series = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| charts.get(*key).and_then(|values| values.as_array()))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let pac = series(&["pac", "pacChart"]);
+    let ppv = series(&["ppv", "ppvChart"]);
+
+    let points = pac.len().max(ppv.len());
+    if points == 0 {
+        return Vec::new();
+    }
+    // Over one point a second, the points past the end of the day are dropped
+    let step = (24 * 60 * 60 / points).max(1) as u32;
+
+    (0..points)
+        .filter_map(|index| {
+            Some(PowerSample {
+                time: NaiveTime::from_num_seconds_from_midnight_opt(index as u32 * step, 0)?,
+                pac: pac.get(index).and_then(value_as_f64),
+                ppv: ppv.get(index).and_then(value_as_f64),
+            })
+        })
+        .collect()
+}
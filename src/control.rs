@@ -0,0 +1,482 @@
+use chrono::{DateTime, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::{response_obj, value_as_bool, value_as_f64, Growatt, GrowattError, Result};
+
+/// Number of charge and discharge time slots a MIX inverter supports.
+const MIX_TIME_SLOTS: usize = 3;
+
+/// A single `tcpSet.do` write: the setting type and its positional
+/// `param1..paramN` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetCommand {
+    action: String,
+    param_type: String,
+    params: Vec<String>,
+}
+
+impl SetCommand {
+    /// Creates a MIX (`mixSet`) command with raw positional parameters.
+    pub fn new(param_type: &str, params: &[&str]) -> Self {
+        Self {
+            action: "mixSet".to_string(),
+            param_type: param_type.to_string(),
+            params: params.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Limits the active power output to a percentage of the rated power.
+    /// Fails with [`GrowattError::InvalidInput`] for a rate over 100.
+    pub fn active_power_limit(rate: u8) -> Result<Self> {
+        Ok(Self::from_params("pv_active_p_rate", vec![percent("Active power limit", rate)?]))
+    }
+
+    /// Sets the inverter clock. The inverter has no notion of time zones, so the
+    /// wall-clock time is sent as is.
+    pub fn time_sync(time: NaiveDateTime) -> Self {
+        Self::new("pf_sys_year", &[&time.format("%Y-%m-%d %H:%M:%S").to_string()])
+    }
+
+    /// Battery-first mode: charges the battery during `periods` at
+    /// `charge_rate`% of the rated power until it reaches `stop_soc`%, from the
+    /// grid too when `ac_charge` is set.
+    ///
+    /// The inverter has three time slots; extra periods are ignored and unused
+    /// slots are sent disabled. Fails with [`GrowattError::InvalidInput`] for
+    /// a rate or SOC over 100.
+    pub fn battery_first_schedule(charge_rate: u8, stop_soc: u8, ac_charge: bool, periods: &[TimePeriod]) -> Result<Self> {
+        let mut params = vec![percent("Charge rate", charge_rate)?, percent("Stop SOC", stop_soc)?, flag(ac_charge)];
+        params.extend(slot_params(periods));
+        Ok(Self::from_params("mix_ac_charge_time_period", params))
+    }
+
+    /// Grid-first mode: discharges the battery to the grid during `periods`
+    /// at `discharge_rate`% of the rated power until it falls to `stop_soc`%.
+    ///
+    /// Periods and out-of-range values are handled as for
+    /// [`SetCommand::battery_first_schedule`].
+    pub fn grid_first_schedule(discharge_rate: u8, stop_soc: u8, periods: &[TimePeriod]) -> Result<Self> {
+        let mut params = vec![percent("Discharge rate", discharge_rate)?, percent("Stop SOC", stop_soc)?];
+        params.extend(slot_params(periods));
+        Ok(Self::from_params("mix_ac_discharge_time_period", params))
+    }
+
+    /// AC charge schedule of an AC-coupled (SPA) inverter: charges the battery
+    /// from the grid during `periods` at `charge_rate`% of the rated power
+    /// until it reaches `stop_soc`%.
+    ///
+    /// Periods and out-of-range values are handled as for
+    /// [`SetCommand::battery_first_schedule`].
+    pub fn ac_charge_schedule(charge_rate: u8, stop_soc: u8, periods: &[TimePeriod]) -> Result<Self> {
+        let mut params = vec![percent("Charge rate", charge_rate)?, percent("Stop SOC", stop_soc)?];
+        params.extend(slot_params(periods));
+        Ok(Self::from_params("spa_ac_charge_time_period", params).with_action("spaSet"))
+    }
+
+    /// Switches the inverter on or off.
+    pub fn on_off(on: bool) -> Self {
+        Self::new("pv_on_off", &[if on { "0001" } else { "0000" }])
+    }
+
+    fn from_params(param_type: &str, params: Vec<String>) -> Self {
+        Self {
+            action: "mixSet".to_string(),
+            param_type: param_type.to_string(),
+            params,
+        }
+    }
+
+    /// Overrides the `action` sent to `tcpSet.do`, which depends on the device
+    /// family, e.g. `maxSet` for MAX or `tlxSet` for TLX inverters.
+    pub fn with_action(mut self, action: &str) -> Self {
+        self.action = action.to_string();
+        self
+    }
+
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    pub fn param_type(&self) -> &str {
+        &self.param_type
+    }
+
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    // Whether the read-back settings reflect this command, or None for
+    // settings that can't be read back
+    pub(crate) fn applied_in(&self, settings: &MixSettings) -> Option<bool> {
+        let number = |index: usize| self.params.get(index)?.parse::<f64>().ok();
+        let flag = |index: usize| self.params.get(index).map(|value| value == "1");
+
+        let applied = match (self.action.as_str(), self.param_type.as_str()) {
+            ("mixSet", "pv_active_p_rate") => settings.active_power_rate == number(0),
+            ("mixSet", "pv_on_off") => settings.on_off == Some(self.params.first()? == "0001"),
+            ("mixSet", "mix_ac_charge_time_period") => {
+                settings.charge_power_rate == number(0)
+                    && settings.charge_stop_soc == number(1)
+                    && settings.ac_charge_enabled == flag(2)
+                    && same_slots(&settings.charge_periods, &periods_from_params(self.params.get(3..).unwrap_or_default()))
+            }
+            ("mixSet", "mix_ac_discharge_time_period") => {
+                settings.discharge_power_rate == number(0)
+                    && settings.discharge_stop_soc == number(1)
+                    && same_slots(&settings.discharge_periods, &periods_from_params(self.params.get(2..).unwrap_or_default()))
+            }
+            _ => return None,
+        };
+        Some(applied)
+    }
+
+    fn to_form(&self, serial_num: &str) -> Vec<(String, String)> {
+        let mut form = vec![
+            ("action".to_string(), self.action.clone()),
+            ("serialNum".to_string(), serial_num.to_string()),
+            ("type".to_string(), self.param_type.clone()),
+        ];
+        form.extend(
+            self.params
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (format!("param{}", i + 1), value.clone())),
+        );
+        form
+    }
+}
+
+// Rejects out-of-range values rather than writing a different setting than asked
+fn percent(name: &str, value: u8) -> Result<String> {
+    if value > 100 {
+        return Err(GrowattError::InvalidInput(format!("{} must be at most 100%, got {}%", name, value)));
+    }
+    Ok(value.to_string())
+}
+
+fn flag(enabled: bool) -> String {
+    if enabled { "1" } else { "0" }.to_string()
+}
+
+// Each slot is sent as start hour, start minute, end hour, end minute and an
+// enabled flag, for all MIX_TIME_SLOTS slots
+fn slot_params(periods: &[TimePeriod]) -> Vec<String> {
+    let unused = TimePeriod {
+        start: NaiveTime::MIN,
+        end: NaiveTime::MIN,
+        enabled: false,
+    };
+
+    periods
+        .iter()
+        .chain(std::iter::repeat(&unused))
+        .take(MIX_TIME_SLOTS)
+        .flat_map(|period| {
+            [
+                period.start.hour().to_string(),
+                period.start.minute().to_string(),
+                period.end.hour().to_string(),
+                period.end.minute().to_string(),
+                flag(period.enabled),
+            ]
+        })
+        .collect()
+}
+
+// Inverse of slot_params; malformed slots are left empty
+fn periods_from_params(params: &[String]) -> MixSlots {
+    let mut slots = MixSlots::default();
+    for (slot, values) in slots.iter_mut().zip(params.chunks_exact(5)) {
+        *slot = slot_from_params(values);
+    }
+    slots
+}
+
+fn slot_from_params(values: &[String]) -> Option<TimePeriod> {
+    let number = |index: usize| values[index].parse::<u32>().ok();
+    Some(TimePeriod {
+        start: NaiveTime::from_hms_opt(number(0)?, number(1)?, 0)?,
+        end: NaiveTime::from_hms_opt(number(2)?, number(3)?, 0)?,
+        enabled: values[4] == "1",
+    })
+}
+
+// Compares slot by slot. Disabled and missing slots are equivalent, as the
+// portal may report any time for a disabled slot.
+fn same_slots(a: &MixSlots, b: &MixSlots) -> bool {
+    let enabled = |slot: &Option<TimePeriod>| slot.clone().filter(|period| period.enabled);
+    a.iter().zip(b).all(|(a, b)| enabled(a) == enabled(b))
+}
+
+/// The time slots of a MIX inverter schedule, by position: index 0 is the
+/// inverter's first slot. `None` where the portal reports no usable slot.
+pub type MixSlots = [Option<TimePeriod>; MIX_TIME_SLOTS];
+
+/// A configured time window on the inverter, e.g. an AC charge period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimePeriod {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub enabled: bool,
+}
+
+/// The charge schedule of a MIX inverter: the battery-first periods that
+/// charge the battery and the grid-first periods that discharge it, written
+/// together with [`Growatt::set_mix_charge_schedule`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChargeSchedule {
+    /// Charge power as a percentage of the rated power.
+    pub charge_rate: u8,
+    /// Battery SOC (%) at which charging stops.
+    pub target_soc: u8,
+    /// Whether the battery may be charged from the grid.
+    pub ac_charge: bool,
+    /// Up to three battery-first periods; unused slots are sent disabled.
+    pub battery_first_periods: Vec<TimePeriod>,
+    /// Discharge power as a percentage of the rated power.
+    pub discharge_rate: u8,
+    /// Battery SOC (%) at which discharging stops.
+    pub discharge_stop_soc: u8,
+    /// Up to three grid-first periods; unused slots are sent disabled.
+    pub grid_first_periods: Vec<TimePeriod>,
+}
+
+impl ChargeSchedule {
+    /// Checks the schedule can be written as is, so a typo doesn't silently
+    /// end up as a clamped or truncated setting on the inverter.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(GrowattError::InvalidInput(message));
+
+        let limits = [
+            ("Charge rate", self.charge_rate),
+            ("Target SOC", self.target_soc),
+            ("Discharge rate", self.discharge_rate),
+            ("Discharge stop SOC", self.discharge_stop_soc),
+        ];
+        for (name, value) in limits {
+            if value > 100 {
+                return invalid(format!("{} must be at most 100%, got {}%", name, value));
+            }
+        }
+
+        for (mode, periods) in [("battery-first", &self.battery_first_periods), ("grid-first", &self.grid_first_periods)] {
+            if periods.len() > MIX_TIME_SLOTS {
+                return invalid(format!(
+                    "A MIX inverter supports at most {} {} periods, got {}",
+                    MIX_TIME_SLOTS,
+                    mode,
+                    periods.len()
+                ));
+            }
+            // Disabled slots are sent as placeholders, so only enabled ones
+            // need a usable range
+            for period in periods.iter().filter(|period| period.enabled) {
+                if period.start >= period.end {
+                    return invalid(format!(
+                        "A {} period must start before it ends, got {}-{}",
+                        mode,
+                        period.start.format("%H:%M"),
+                        period.end.format("%H:%M")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn to_commands(&self) -> Result<[SetCommand; 2]> {
+        Ok([
+            SetCommand::battery_first_schedule(self.charge_rate, self.target_soc, self.ac_charge, &self.battery_first_periods)?,
+            SetCommand::grid_first_schedule(self.discharge_rate, self.discharge_stop_soc, &self.grid_first_periods)?,
+        ])
+    }
+}
+
+/// The settings currently configured on a MIX inverter, as read back from the
+/// portal. Fields mirror what the `tcpSet.do` setters write.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MixSettings {
+    /// Charge power as a percentage of the rated power.
+    pub charge_power_rate: Option<f64>,
+    /// Battery SOC (%) at which charging stops.
+    pub charge_stop_soc: Option<f64>,
+    /// Whether the battery may be charged from the grid.
+    pub ac_charge_enabled: Option<bool>,
+    pub charge_periods: MixSlots,
+    /// Discharge power as a percentage of the rated power.
+    pub discharge_power_rate: Option<f64>,
+    /// Battery SOC (%) at which discharging stops.
+    pub discharge_stop_soc: Option<f64>,
+    pub discharge_periods: MixSlots,
+    /// Active power limit as a percentage of the rated power.
+    pub active_power_rate: Option<f64>,
+    /// Whether the inverter is switched on.
+    pub on_off: Option<bool>,
+}
+
+impl MixSettings {
+    pub(crate) fn from_value(settings: &serde_json::Value) -> Self {
+        let number = |key: &str| settings.get(key).and_then(value_as_f64);
+
+        Self {
+            charge_power_rate: number("chargePowerCommand"),
+            charge_stop_soc: number("wchargeSOCLowLimit2"),
+            ac_charge_enabled: settings.get("acChargeEnable").and_then(value_as_bool),
+            charge_periods: parse_periods(settings, "forcedChargeTimeStart", "forcedChargeTimeStop", "forcedChargeStopSwitch"),
+            discharge_power_rate: number("disChargePowerCommand"),
+            discharge_stop_soc: number("wdisChargeSOCLowLimit2"),
+            discharge_periods: parse_periods(settings, "forcedDischargeTimeStart", "forcedDischargeTimeStop", "forcedDischargeStopSwitch"),
+            active_power_rate: number("activeRate"),
+            on_off: settings.get("onOff").and_then(value_as_bool),
+        }
+    }
+}
+
+// The portal reports times as "H:M" without zero padding
+fn parse_time(value: &serde_json::Value) -> Option<NaiveTime> {
+    let (hour, minute) = value.as_str()?.trim().split_once(':')?;
+    NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, 0)
+}
+
+// Periods are spread over numbered keys, e.g. forcedChargeTimeStart1..3, and
+// keep their slot position
+fn parse_periods(settings: &serde_json::Value, start_key: &str, end_key: &str, switch_key: &str) -> MixSlots {
+    std::array::from_fn(|index| {
+        let slot = index + 1;
+        let start = settings.get(format!("{}{}", start_key, slot))?;
+        let end = settings.get(format!("{}{}", end_key, slot))?;
+        Some(TimePeriod {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+            enabled: settings
+                .get(format!("{}{}", switch_key, slot))
+                .and_then(value_as_bool)
+                .unwrap_or(false),
+        })
+    })
+}
+
+impl Growatt {
+    /// Applies a setting to a device and returns whether the server accepted it.
+    pub async fn set_param(&mut self, device_sn: &str, setting: &SetCommand) -> Result<bool> {
+        self.check_login().await?;
+        self.send_set_command(device_sn, setting).await
+    }
+
+    /// Applies a setting to a MIX inverter and confirms it took effect: after
+    /// the server accepts it, waits `settle` and reads the settings back with
+    /// [`Growatt::get_mix_settings`].
+    ///
+    /// Returns `Ok(false)` if the server rejected the setting and
+    /// [`GrowattError::NotApplied`] if it was acknowledged but the inverter
+    /// still reports the old value. Only settings that
+    /// [`MixSettings`] reads back can be verified; others fail with
+    /// [`GrowattError::InvalidInput`] before anything is sent.
+    pub async fn set_and_verify(&mut self, device_sn: &str, setting: &SetCommand, settle: std::time::Duration) -> Result<bool> {
+        if setting.applied_in(&MixSettings::default()).is_none() {
+            return Err(GrowattError::InvalidInput(format!(
+                "Setting {} can't be read back for verification",
+                setting.param_type()
+            )));
+        }
+
+        if !self.set_param(device_sn, setting).await? {
+            return Ok(false);
+        }
+
+        tokio::time::sleep(settle).await;
+
+        let settings = self.get_mix_settings(device_sn).await?;
+        if setting.applied_in(&settings) == Some(true) {
+            Ok(true)
+        } else {
+            Err(GrowattError::NotApplied {
+                param_type: setting.param_type().to_string(),
+            })
+        }
+    }
+
+    /// Sets the inverter clock to the given time, expressed in its own time
+    /// zone. Pass the plant's local time to correct an inverter regardless of
+    /// where this client runs.
+    pub async fn set_device_time<Tz: TimeZone>(&mut self, device_sn: &str, datetime: DateTime<Tz>) -> Result<bool> {
+        self.set_param(device_sn, &SetCommand::time_sync(datetime.naive_local())).await
+    }
+
+    /// Writes a MIX (`mixSet`) setting with raw named parameters, e.g.
+    /// `[("param1", "50")]`, and returns whether the server accepted it.
+    ///
+    /// An escape hatch for settings without a [`SetCommand`] constructor;
+    /// prefer [`Growatt::set_param`] where one exists.
+    pub async fn set_inverter_param(&mut self, serial_num: &str, param_type: &str, params: &[(&str, &str)]) -> Result<bool> {
+        self.check_login().await?;
+
+        let mut form = vec![("action", "mixSet"), ("serialNum", serial_num), ("type", param_type)];
+        form.extend_from_slice(params);
+        self.post_tcp_set(&form).await
+    }
+
+    /// Writes a charge schedule to a MIX inverter, battery-first half then
+    /// grid-first half, and returns whether the server accepted both.
+    ///
+    /// The schedule is validated first; bad input fails with
+    /// [`GrowattError::InvalidInput`] before anything is sent. If the
+    /// battery-first write is rejected, nothing is applied and `Ok(false)` is
+    /// returned. If the grid-first write then fails or is rejected, the
+    /// battery-first half stays applied and
+    /// [`GrowattError::PartiallyApplied`] says so.
+    pub async fn set_mix_charge_schedule(&mut self, mix_sn: &str, schedule: &ChargeSchedule) -> Result<bool> {
+        schedule.validate()?;
+        let [battery_first, grid_first] = schedule.to_commands()?;
+
+        if !self.set_param(mix_sn, &battery_first).await? {
+            return Ok(false);
+        }
+
+        let source = match self.set_param(mix_sn, &grid_first).await {
+            Ok(true) => return Ok(true),
+            Ok(false) => None,
+            Err(e) => Some(Box::new(e)),
+        };
+        Err(GrowattError::PartiallyApplied {
+            applied: "battery-first".to_string(),
+            failed: "grid-first".to_string(),
+            source,
+        })
+    }
+
+    // Assumes the session has already been checked
+    pub(crate) async fn send_set_command(&self, device_sn: &str, setting: &SetCommand) -> Result<bool> {
+        let form = setting.to_form(device_sn);
+        let form: Vec<(&str, &str)> = form.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+        self.post_tcp_set(&form).await
+    }
+
+    // Every write goes through tcpSet.do, which reports the outcome in "success"
+    async fn post_tcp_set(&self, form: &[(&str, &str)]) -> Result<bool> {
+        let json_response = self.fetch_form("/tcpSet.do", form).await?;
+
+        json_response
+            .get("success")
+            .and_then(value_as_bool)
+            .ok_or_else(|| GrowattError::InvalidResponse("Invalid response structure".to_string()))
+    }
+
+    /// Reads back the settings currently configured on a MIX inverter.
+    ///
+    /// Use this after a `tcpSet.do` write to confirm the inverter actually
+    /// applied the change.
+    pub async fn get_mix_settings(&mut self, mix_sn: &str) -> Result<MixSettings> {
+        let form = [("serialNum", mix_sn)];
+        let obj = response_obj(self.post_form("/device/getMixSetParams", &form).await?)?;
+
+        // Newer portal versions nest the settings in a "mixBean" object
+        let settings = obj.get("mixBean").unwrap_or(&obj);
+        if !settings.is_object() {
+            return Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            ));
+        }
+        Ok(MixSettings::from_value(settings))
+    }
+}
@@ -0,0 +1,585 @@
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use chrono::{FixedOffset, Local, NaiveDateTime, Utc};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::{deserialize_lenient_bool, deserialize_lenient_datetime, deserialize_lenient_f64, deserialize_lenient_i64, from_result_value};
+use crate::{check_plant_access, value_as_bool, value_as_f64, Growatt, GrowattError, Result};
+
+/// How many plants are searched at once when looking a device up by serial.
+const DEVICE_SEARCH_CONCURRENCY: usize = 8;
+
+/// A device entry from a plant's device list.
+///
+/// Which fields are filled depends on the device type, so all but the serial
+/// are optional.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    #[serde(rename = "deviceSn")]
+    pub device_sn: String,
+    /// Device family as the portal names it, e.g. `"mix"`, `"tlx"` or `"max"`.
+    #[serde(rename = "deviceType", default)]
+    pub device_type: Option<String>,
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(default, alias = "deviceStatus", deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+    /// Whether the datalogger has lost its connection to the server.
+    #[serde(default, deserialize_with = "deserialize_lenient_bool")]
+    pub lost: Option<bool>,
+    /// Time of the last report, in the plant's local time.
+    #[serde(rename = "lastUpdateTime", default, deserialize_with = "deserialize_lenient_datetime")]
+    pub last_update_time: Option<NaiveDateTime>,
+    #[serde(rename = "datalogSn", default)]
+    pub datalogger_sn: Option<String>,
+    /// Current output power (W).
+    #[serde(default, alias = "pac", deserialize_with = "deserialize_lenient_f64")]
+    pub power: Option<f64>,
+}
+
+impl Device {
+    /// The device's state from its `lost` flag and `status` code, see
+    /// [`resolve_device_state`]. `None` if the entry reports neither.
+    pub fn state(&self) -> Option<DeviceState> {
+        device_state(self.lost, self.status)
+    }
+}
+
+/// The true state of a device, combining the device list's `lost` flag with
+/// its `status` code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeviceState {
+    /// The datalogger lost its connection, or the device reports itself offline.
+    Offline,
+    /// Connected but not producing: waiting for enough PV, or self-checking.
+    Standby,
+    Producing,
+    Fault,
+    /// A status code this crate doesn't know.
+    Unknown(i64),
+}
+
+/// Resolves a device's state from the device list's `lost` flag and
+/// `status`/`deviceStatus` code.
+///
+/// `lost` wins over the status: the portal keeps the last status a device
+/// reported, so a device that dropped off while producing still shows
+/// `status: 1` next to `lost: true`. Otherwise the status decides:
+///
+/// | Status | State       |
+/// |--------|-------------|
+/// | -1     | `Offline`   |
+/// | 0      | `Standby`   |
+/// | 1      | `Producing` |
+/// | 2      | `Standby`   |
+/// | 3      | `Fault`     |
+pub fn resolve_device_state(lost: bool, status: i64) -> DeviceState {
+    if lost {
+        return DeviceState::Offline;
+    }
+    match status {
+        -1 => DeviceState::Offline,
+        0 | 2 => DeviceState::Standby,
+        1 => DeviceState::Producing,
+        3 => DeviceState::Fault,
+        other => DeviceState::Unknown(other),
+    }
+}
+
+// Shared by the typed device structs, where either field may be missing
+pub(crate) fn device_state(lost: Option<bool>, status: Option<i64>) -> Option<DeviceState> {
+    match (lost, status) {
+        (Some(true), _) => Some(DeviceState::Offline),
+        (_, Some(status)) => Some(resolve_device_state(false, status)),
+        _ => None,
+    }
+}
+
+/// Converts a raw device list entry.
+impl TryFrom<&serde_json::Value> for Device {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+/// A plant's devices, see [`Growatt::get_devices`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceList(pub Vec<Device>);
+
+impl DeviceList {
+    /// Looks a device up by serial number, ignoring case.
+    pub fn find(&self, device_sn: &str) -> Option<&Device> {
+        self.0.iter().find(|device| device.device_sn.eq_ignore_ascii_case(device_sn))
+    }
+
+    /// Devices of one family, e.g. `"mix"`, as named in
+    /// [`Device::device_type`].
+    pub fn of_type(&self, device_type: &str) -> Vec<&Device> {
+        self.0
+            .iter()
+            .filter(|device| device.device_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(device_type)))
+            .collect()
+    }
+}
+
+/// One page of a plant's device list, see [`Growatt::get_device_page`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevicePage {
+    pub devices: Vec<serde_json::Value>,
+    /// The page number, counting from 1.
+    pub page: u32,
+    /// Total number of pages, if the server reported it.
+    pub pages: Option<u32>,
+    /// Total number of devices over all pages, if the server reported it.
+    pub count: Option<u64>,
+}
+
+impl DevicePage {
+    /// Reads a `getDevicesByPlantList` response. Missing fields leave the
+    /// page empty rather than failing.
+    pub fn from_response(json: &serde_json::Value, page: u32) -> Self {
+        let obj = json.get("obj");
+        let field = |name: &str| obj.and_then(|obj| obj.get(name)).and_then(value_as_f64);
+
+        Self {
+            devices: obj
+                .and_then(|obj| obj.get("datas"))
+                .and_then(|datas| datas.as_array())
+                .cloned()
+                .unwrap_or_default(),
+            page,
+            pages: field("pages").map(|pages| pages as u32),
+            count: field("count").map(|count| count as u64),
+        }
+    }
+
+    /// Whether a later page may hold more devices.
+    ///
+    /// A short page doesn't end the list, as the portal's page sizes vary,
+    /// but an empty page or a zero total does whatever the page count says.
+    pub fn has_next(&self) -> bool {
+        !self.devices.is_empty() && self.count != Some(0) && self.page < self.pages.unwrap_or(1)
+    }
+}
+
+/// Streams a plant's devices page by page, see
+/// [`Growatt::stream_plant_devices`].
+pub struct DevicePageStream<'a> {
+    inner: BoxStream<'a, Result<serde_json::Value>>,
+}
+
+impl Stream for DevicePageStream<'_> {
+    type Item = Result<serde_json::Value>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+// State threaded through the device stream
+struct DeviceCursor<'a> {
+    client: &'a mut Growatt,
+    plant_id: &'a str,
+    page: u32,
+    pending: VecDeque<serde_json::Value>,
+    session_checked: bool,
+    done: bool,
+}
+
+/// A device family, as the portal groups devices in `getDevicesByPlant`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeviceType {
+    /// Grid-tied string inverter.
+    Inverter,
+    Mix,
+    Max,
+    Tlx,
+    /// Off-grid SPF storage inverter.
+    Storage,
+    Spa,
+    Sph,
+    /// A family this crate doesn't know, with the portal's name for it.
+    Other(String),
+}
+
+impl DeviceType {
+    /// Maps the portal's name for a device family, e.g. `"mix"` or `"tlx"`.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "inv" | "inverter" => DeviceType::Inverter,
+            "mix" => DeviceType::Mix,
+            "max" => DeviceType::Max,
+            "tlx" => DeviceType::Tlx,
+            "storage" => DeviceType::Storage,
+            "spa" => DeviceType::Spa,
+            "sph" => DeviceType::Sph,
+            _ => DeviceType::Other(name.to_string()),
+        }
+    }
+}
+
+/// A plant's devices as `getDevicesByPlant` groups them, see
+/// [`Growatt::get_devices_by_type`]. Families the plant has none of are empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DevicesByType {
+    #[serde(default)]
+    pub mix: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub tlx: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub max: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub storage: Vec<serde_json::Value>,
+}
+
+// getDevicesByPlant groups devices by family, with each entry either an
+// object carrying deviceSn or an array starting with the serial
+pub(crate) fn classify_devices(obj: &serde_json::Value) -> HashMap<String, DeviceType> {
+    let Some(groups) = obj.as_object() else {
+        return HashMap::new();
+    };
+
+    groups
+        .iter()
+        .filter_map(|(name, entries)| Some((DeviceType::from_name(name), entries.as_array()?)))
+        .flat_map(|(device_type, entries)| {
+            entries.iter().filter_map(move |entry| {
+                let serial = match entry {
+                    serde_json::Value::Array(fields) => fields.first()?.as_str()?,
+                    entry => entry.get("deviceSn")?.as_str()?,
+                };
+                Some((serial.to_string(), device_type.clone()))
+            })
+        })
+        .collect()
+}
+
+// Whether a device list entry is reporting: neither offline nor faulted per
+// resolve_device_state, and updated within the threshold. The portal reports
+// update times in the plant's local time, so `now` must be too, see
+// plant_local_now.
+pub(crate) fn is_device_reporting(device: &serde_json::Value, now: NaiveDateTime, threshold: chrono::Duration) -> bool {
+    let lost = device.get("lost").and_then(value_as_bool);
+    let status = device
+        .get("status")
+        .or_else(|| device.get("deviceStatus"))
+        .and_then(value_as_f64)
+        .map(|status| status as i64);
+    if matches!(device_state(lost, status), Some(DeviceState::Offline | DeviceState::Fault)) {
+        return false;
+    }
+
+    // Without an update time only the status flags can be judged
+    device
+        .get("lastUpdateTime")
+        .and_then(|time| time.as_str())
+        .and_then(|time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").ok())
+        .is_none_or(|updated| now - updated <= threshold)
+}
+
+// The plant's current local time, from the UTC offset in hours its
+// getPlantData payload carries as `timezone`. Plants that don't report one
+// fall back to this machine's local time.
+pub(crate) fn plant_local_now(plant_obj: &serde_json::Value) -> NaiveDateTime {
+    let offset = plant_obj
+        .get("timezone")
+        .and_then(value_as_f64)
+        .and_then(|hours| FixedOffset::east_opt((hours * 3600.0).round() as i32));
+
+    match offset {
+        Some(offset) => Utc::now().with_timezone(&offset).naive_local(),
+        None => Local::now().naive_local(),
+    }
+}
+
+impl Growatt {
+    /// Finds the plant a device belongs to, given only its serial number.
+    ///
+    /// Searches the device lists of all the account's plants concurrently and
+    /// returns the first matching plant ID, or `None` if no plant lists the
+    /// device. If the device wasn't found and a plant's device list couldn't be
+    /// fetched, that error is returned since the device may be on that plant.
+    pub async fn find_plant_for_device(&mut self, device_sn: &str) -> Result<Option<String>> {
+        let found = self
+            .search_plant_devices(|devices| {
+                devices
+                    .iter()
+                    .any(|device| {
+                        device
+                            .get("deviceSn")
+                            .and_then(|sn| sn.as_str())
+                            .is_some_and(|sn| sn.eq_ignore_ascii_case(device_sn))
+                    })
+                    .then_some(())
+            })
+            .await?;
+
+        Ok(found.map(|(plant_id, ())| plant_id))
+    }
+
+    /// Lists the devices attached to a datalogger, given its serial number.
+    ///
+    /// A datalogger belongs to one plant, so the search stops at the first
+    /// plant with matching devices. Returns an empty list if none is found;
+    /// errors are handled as in [`Growatt::find_plant_for_device`].
+    pub async fn get_devices_by_datalogger(&mut self, datalog_sn: &str) -> Result<Vec<Device>> {
+        let found = self
+            .search_plant_devices(|devices| {
+                let attached: Vec<Device> = devices
+                    .iter()
+                    .filter(|device| {
+                        device
+                            .get("datalogSn")
+                            .and_then(|sn| sn.as_str())
+                            .is_some_and(|sn| sn.eq_ignore_ascii_case(datalog_sn))
+                    })
+                    .filter_map(|device| serde_json::from_value(device.clone()).ok())
+                    .collect();
+                (!attached.is_empty()).then_some(attached)
+            })
+            .await?;
+
+        Ok(found.map(|(_, devices)| devices).unwrap_or_default())
+    }
+
+    // Searches the device lists of all plants concurrently, returning the first
+    // plant for which `matcher` finds something
+    async fn search_plant_devices<T, F>(&mut self, matcher: F) -> Result<Option<(String, T)>>
+    where
+        F: Fn(&[serde_json::Value]) -> Option<T>,
+    {
+        let plants = self.get_plants().await?;
+
+        let this = &*self;
+        let mut searches = stream::iter(plants.0)
+            .map(|plant| async move {
+                let devices = this.fetch_plant_devices(&plant.plant_id).await;
+                (plant.plant_id, devices)
+            })
+            .buffer_unordered(DEVICE_SEARCH_CONCURRENCY);
+
+        let mut first_error = None;
+        while let Some((plant_id, devices)) = searches.next().await {
+            match devices {
+                Ok(devices) => {
+                    if let Some(found) = matcher(&devices) {
+                        return Ok(Some((plant_id, found)));
+                    }
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    /// Maps every device of a plant to its type, from a single
+    /// `getDevicesByPlant` fetch.
+    pub async fn classify_plant_devices(&mut self, plant_id: &str) -> Result<HashMap<String, DeviceType>> {
+        let obj = self.fetch_devices_by_plant(plant_id).await?;
+        Ok(classify_devices(&obj))
+    }
+
+    /// Fetches the plant's devices grouped by family, the general form of
+    /// [`Growatt::get_mix_ids`]. Entries are kept raw, as each family reports
+    /// different fields.
+    pub async fn get_devices_by_type(&mut self, plant_id: &str) -> Result<DevicesByType> {
+        let mut obj = self.fetch_devices_by_plant(plant_id).await?;
+        // Families without devices may come back as null rather than []
+        if let Some(groups) = obj.as_object_mut() {
+            groups.retain(|_, entries| entries.is_array());
+        }
+        Ok(serde_json::from_value(obj)?)
+    }
+
+    // The obj of getDevicesByPlant, keyed by device family
+    async fn fetch_devices_by_plant(&mut self, plant_id: &str) -> Result<serde_json::Value> {
+        let json_response: serde_json::Value = self
+            .post_form(&format!("/panel/getDevicesByPlant?plantId={}", plant_id), &[])
+            .await?;
+        check_plant_access(&json_response, plant_id)?;
+
+        match json_response.get("obj") {
+            Some(obj) => Ok(obj.clone()),
+            None => Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            )),
+        }
+    }
+
+    /// Reports whether the plant is online: at least one device is neither
+    /// offline nor faulted (see [`resolve_device_state`]) and has reported
+    /// within the staleness threshold set with
+    /// [`Growatt::with_online_threshold`], judged in the plant's time zone.
+    pub async fn is_plant_online(&mut self, plant_id: &str) -> Result<bool> {
+        self.check_login().await?;
+        self.fetch_plant_online(plant_id).await
+    }
+
+    // Judges a plant as is_plant_online does, over an already-checked session
+    async fn fetch_plant_online(&self, plant_id: &str) -> Result<bool> {
+        let plant_obj = self.fetch_plant_obj(plant_id).await?;
+        self.fetch_plant_online_at(plant_id, plant_local_now(&plant_obj)).await
+    }
+
+    // Whether any of the plant's devices is reporting, given the plant's
+    // local time
+    pub(crate) async fn fetch_plant_online_at(&self, plant_id: &str, now: NaiveDateTime) -> Result<bool> {
+        let devices = self.fetch_plant_devices(plant_id).await?;
+        Ok(devices
+            .iter()
+            .any(|device| is_device_reporting(device, now, self.online_threshold)))
+    }
+
+    /// Polls a device until it reports as online, e.g. after a restart
+    /// command, judging it as [`Growatt::is_plant_online`] does.
+    ///
+    /// Checks right away and then every `poll_interval`, and returns `false`
+    /// if the device is still not reporting once `timeout` has elapsed.
+    pub async fn wait_until_online(
+        &mut self,
+        plant_id: &str,
+        device_sn: &str,
+        timeout: std::time::Duration,
+        poll_interval: std::time::Duration,
+    ) -> Result<bool> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        self.check_login().await?;
+        let plant_obj = self.fetch_plant_obj(plant_id).await?;
+
+        loop {
+            self.check_login().await?;
+
+            let devices = self.fetch_plant_devices(plant_id).await?;
+            let now = plant_local_now(&plant_obj);
+            let online = devices.iter().any(|device| {
+                device
+                    .get("deviceSn")
+                    .and_then(|sn| sn.as_str())
+                    .is_some_and(|sn| sn.eq_ignore_ascii_case(device_sn))
+                    && is_device_reporting(device, now, self.online_threshold)
+            });
+            if online {
+                return Ok(true);
+            }
+
+            if tokio::time::Instant::now() + poll_interval > deadline {
+                return Ok(false);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Fetches one page of a plant's device list, with its page and device
+    /// totals.
+    pub async fn get_device_page(&mut self, plant_id: &str, page: u32) -> Result<DevicePage> {
+        self.check_login().await?;
+        self.fetch_device_page(plant_id, page).await
+    }
+
+    /// Fetches every page of a plant's device list.
+    pub async fn get_all_devices_by_plant(&mut self, plant_id: &str) -> Result<Vec<serde_json::Value>> {
+        self.check_login().await?;
+        self.fetch_plant_devices(plant_id).await
+    }
+
+    /// Fetches every page of a plant's device list as typed entries.
+    ///
+    /// Entries without a serial number are skipped.
+    pub async fn get_devices(&mut self, plant_id: &str) -> Result<DeviceList> {
+        let devices = self.get_all_devices_by_plant(plant_id).await?;
+        Ok(DeviceList(
+            devices
+                .iter()
+                .filter_map(|device| Device::try_from(device).ok())
+                .collect(),
+        ))
+    }
+
+    /// Streams a plant's devices, fetching the next page only once the
+    /// previous one has been consumed. The stream ends after the first error.
+    pub fn stream_plant_devices<'a>(&'a mut self, plant_id: &'a str) -> DevicePageStream<'a> {
+        let cursor = DeviceCursor {
+            client: self,
+            plant_id,
+            page: 1,
+            pending: VecDeque::new(),
+            session_checked: false,
+            done: false,
+        };
+
+        let inner = stream::unfold(cursor, |mut cursor| async move {
+            loop {
+                if let Some(device) = cursor.pending.pop_front() {
+                    return Some((Ok(device), cursor));
+                }
+                if cursor.done {
+                    return None;
+                }
+
+                if let Err(e) = cursor.client.check_page_limit(cursor.page as usize - 1) {
+                    cursor.done = true;
+                    return Some((Err(e), cursor));
+                }
+
+                if !cursor.session_checked {
+                    if let Err(e) = cursor.client.check_login().await {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                    cursor.session_checked = true;
+                }
+
+                match cursor.client.fetch_device_page(cursor.plant_id, cursor.page).await {
+                    Ok(page) => {
+                        cursor.done = !page.has_next();
+                        cursor.page += 1;
+                        cursor.pending.extend(page.devices);
+                    }
+                    Err(e) => {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                }
+            }
+        });
+
+        DevicePageStream { inner: inner.boxed() }
+    }
+
+    async fn fetch_device_page(&self, plant_id: &str, page: u32) -> Result<DevicePage> {
+        let json_response = self.fetch_devices_by_plant_list(plant_id, Some(page as i32)).await?;
+        Ok(DevicePage::from_response(&json_response, page))
+    }
+
+    // Collects the entries from every page of a plant's device list
+    pub(crate) async fn fetch_plant_devices(&self, plant_id: &str) -> Result<Vec<serde_json::Value>> {
+        let mut devices = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let device_page = self.fetch_device_page(plant_id, page).await?;
+            let has_next = device_page.has_next();
+            devices.extend(device_page.devices);
+
+            if !has_next {
+                break;
+            }
+            self.check_page_limit(page as usize)?;
+            page += 1;
+        }
+
+        Ok(devices)
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_f64, Growatt, GrowattError, Result};
+
+/// An entry of a device's operation history, e.g. a setting change or a mode
+/// switch. Distinct from the fault log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceEvent {
+    pub time: NaiveDateTime,
+    pub event_type: String,
+    pub description: String,
+}
+
+impl DeviceEvent {
+    // Entries without a parseable time are dropped
+    pub(crate) fn from_value(record: &serde_json::Value) -> Option<Self> {
+        let text = |key: &str| record.get(key).and_then(|v| v.as_str()).map(str::to_owned);
+
+        Some(Self {
+            time: NaiveDateTime::parse_from_str(record.get("time")?.as_str()?, "%Y-%m-%d %H:%M:%S").ok()?,
+            event_type: text("operType").or_else(|| text("type")).unwrap_or_default(),
+            description: text("content").unwrap_or_default(),
+        })
+    }
+}
+
+impl Growatt {
+    /// Fetches a device's operation history between two dates (inclusive),
+    /// walking all pages of the log. An inverted range fails with
+    /// [`GrowattError::InvalidInput`].
+    pub async fn get_device_event_log(&mut self, device_sn: &str, start: NaiveDate, end: NaiveDate) -> Result<Vec<DeviceEvent>> {
+        if start > end {
+            return Err(GrowattError::InvalidInput(format!(
+                "Start date {} is after end date {}",
+                start, end
+            )));
+        }
+
+        let start = start.format("%Y-%m-%d").to_string();
+        let end = end.format("%Y-%m-%d").to_string();
+
+        let mut events = Vec::new();
+        let mut page = 1;
+        loop {
+            let page_num = page.to_string();
+            let form = [
+                ("deviceSn", device_sn),
+                ("startDate", &start),
+                ("endDate", &end),
+                ("toPageNum", &page_num),
+            ];
+
+            let json_response: serde_json::Value = self.post_form("/log/getDeviceOperationLog", &form).await?;
+
+            let Some(obj) = json_response.get("obj") else {
+                return Err(GrowattError::InvalidResponse(
+                    "Invalid response structure".to_string(),
+                ));
+            };
+
+            let datas = obj.get("datas").and_then(|datas| datas.as_array());
+            if let Some(datas) = datas {
+                events.extend(datas.iter().filter_map(DeviceEvent::from_value));
+            }
+
+            let pages = obj.get("pages").and_then(value_as_f64).unwrap_or(1.0) as i32;
+            if page >= pages || datas.is_none_or(|datas| datas.is_empty()) {
+                break;
+            }
+            self.check_page_limit(page as usize)?;
+            page += 1;
+        }
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{EnergyChart, Result};
+
+/// Writes an energy chart as CSV: a header row with the portal's name for
+/// each series the chart carries, then one row per point of the chart.
+///
+/// Points without data are written as empty cells. Series can differ in
+/// length (the portal sometimes cuts one short), so the shorter ones are
+/// padded with empty cells up to the longest.
+pub fn energy_chart_to_csv(chart: &EnergyChart, writer: impl std::io::Write) -> Result<()> {
+    let series = chart.series();
+
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(series.iter().map(|(name, _)| name))
+        .map_err(std::io::Error::from)?;
+
+    let rows = series.iter().map(|(_, values)| values.len()).max().unwrap_or(0);
+    for row in 0..rows {
+        let cells = series.iter().map(|(_, values)| match values.get(row) {
+            Some(Some(value)) => value.to_string(),
+            _ => String::new(),
+        });
+        csv.write_record(cells).map_err(std::io::Error::from)?;
+    }
+
+    csv.flush()?;
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_f64, Growatt, GrowattError, Result};
+
+/// Severity filter for the plant fault log.
+///
+/// Maps onto the `type` field of `getNewPlantFaultLog`:
+///
+/// | Severity  | `type` |
+/// |-----------|--------|
+/// | `All`     | 0      |
+/// | `Fault`   | 1      |
+/// | `Warning` | 2      |
+/// | `Info`    | 3      |
+///
+/// The `deviceFlag` is always 0 (all device kinds); narrow the log to one
+/// device with the `device_sn` argument instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultSeverity {
+    Info,
+    Warning,
+    Fault,
+    All,
+}
+
+impl FaultSeverity {
+    /// The `type` value the fault log endpoint expects.
+    pub fn fault_type(&self) -> i32 {
+        match self {
+            FaultSeverity::All => 0,
+            FaultSeverity::Fault => 1,
+            FaultSeverity::Warning => 2,
+            FaultSeverity::Info => 3,
+        }
+    }
+
+    /// The `deviceFlag` value the fault log endpoint expects.
+    pub fn device_flag(&self) -> i32 {
+        0
+    }
+}
+
+/// An entry of the plant fault log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultLog {
+    pub device_sn: String,
+    /// When the event occurred. The portal reports the plant's wall-clock
+    /// time, which is read in this machine's time zone.
+    pub time: DateTime<Local>,
+    pub event_id: String,
+    pub event_name: String,
+    /// The portal's suggested fix, when it has one.
+    pub solution: Option<String>,
+    /// The event's `type`, as in [`FaultSeverity::fault_type`].
+    pub fault_type: Option<i32>,
+}
+
+impl FaultLog {
+    // Fails on a missing, malformed or ambiguous (DST change) time, so that an
+    // alert is never filed under the wrong moment
+    pub(crate) fn from_value(record: &serde_json::Value) -> Result<Self> {
+        let text = |key: &str| {
+            record.get(key).and_then(|value| match value {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+        };
+
+        let time = text("time").unwrap_or_default();
+        let time = NaiveDateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .and_then(|time| Local.from_local_datetime(&time).single())
+            .ok_or_else(|| GrowattError::InvalidResponse(format!("Invalid fault log time in {}", record)))?;
+
+        Ok(Self {
+            device_sn: text("deviceSn").or_else(|| text("sn")).unwrap_or_default(),
+            time,
+            event_id: text("eventId").unwrap_or_default(),
+            event_name: text("eventName").unwrap_or_default(),
+            solution: text("eventSolution")
+                .or_else(|| text("solution"))
+                .filter(|solution| !solution.trim().is_empty()),
+            fault_type: record.get("type").and_then(value_as_f64).map(|t| t as i32),
+        })
+    }
+}
+
+/// One page of the plant fault log, see [`Growatt::get_fault_log_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultLogPage {
+    pub logs: Vec<FaultLog>,
+    /// Total number of entries over all pages.
+    pub total: u32,
+    /// The page number, counting from 1.
+    pub page: u32,
+}
+
+impl FaultLogPage {
+    pub(crate) fn from_response(json_response: &serde_json::Value, page: u32) -> Result<Self> {
+        let obj = json_response
+            .get("obj")
+            .filter(|obj| obj.is_object())
+            .ok_or_else(|| GrowattError::InvalidResponse("Invalid response structure".to_string()))?;
+
+        let logs = obj
+            .get("datas")
+            .and_then(|datas| datas.as_array())
+            .map(|datas| datas.iter().map(FaultLog::from_value).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self {
+            total: obj
+                .get("count")
+                .and_then(value_as_f64)
+                .map_or(logs.len() as u32, |count| count as u32),
+            logs,
+            page,
+        })
+    }
+}
+
+impl Growatt {
+    /// Fetches one page of the fault log as typed entries. See
+    /// [`Growatt::get_fault_logs`] for the arguments.
+    pub async fn get_fault_log_page(
+        &mut self,
+        plant_id: &str,
+        date: Option<&str>,
+        device_sn: &str,
+        page: u32,
+        severity: FaultSeverity,
+    ) -> Result<FaultLogPage> {
+        let json_response = self
+            .get_fault_logs_by_severity(plant_id, date, device_sn, page as i32, severity)
+            .await?;
+        FaultLogPage::from_response(&json_response, page)
+    }
+
+    /// Fetches the whole fault log for a day, walking the pages until the
+    /// reported total is reached.
+    pub async fn get_all_fault_logs(
+        &mut self,
+        plant_id: &str,
+        date: Option<&str>,
+        device_sn: &str,
+        severity: FaultSeverity,
+    ) -> Result<Vec<FaultLog>> {
+        let mut logs = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let fault_page = self.get_fault_log_page(plant_id, date, device_sn, page, severity).await?;
+            let empty = fault_page.logs.is_empty();
+            logs.extend(fault_page.logs);
+
+            // An empty page ends the walk even if the total claims more
+            if empty || logs.len() >= fault_page.total as usize {
+                break;
+            }
+            self.check_page_limit(page as usize)?;
+            page += 1;
+        }
+
+        Ok(logs)
+    }
+
+    /// Fetches the fault log filtered by severity, e.g. only faults and not
+    /// warnings. See [`FaultSeverity`] for how severities map to the request.
+    pub async fn get_fault_logs_by_severity(
+        &mut self,
+        plant_id: &str,
+        date: Option<&str>,
+        device_sn: &str,
+        page_num: i32,
+        severity: FaultSeverity,
+    ) -> Result<serde_json::Value> {
+        self.get_fault_logs(
+            plant_id,
+            date,
+            device_sn,
+            page_num,
+            severity.device_flag(),
+            severity.fault_type(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,109 @@
+use futures::stream::{self, StreamExt};
+
+use crate::devices::plant_local_now;
+use crate::{Growatt, GrowattError, Result, SetCommand};
+
+/// A named set of plants, e.g. all sites belonging to one customer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlantGroup {
+    pub name: String,
+    pub plant_ids: Vec<String>,
+}
+
+impl PlantGroup {
+    pub fn new(name: &str, plant_ids: Vec<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            plant_ids,
+        }
+    }
+}
+
+/// Portfolio-level rollup over several plants.
+///
+/// A plant counts as online when at least one of its devices is reporting, as
+/// judged by [`Growatt::is_plant_online`]. Plants whose fetch failed are listed
+/// in `errors` and count as offline; they contribute nothing to the totals.
+#[derive(Debug, Default)]
+pub struct PortfolioSummary {
+    pub current_power: f64,
+    pub today_energy: f64,
+    pub total_energy: f64,
+    pub online: usize,
+    pub offline: usize,
+    pub errors: Vec<(String, GrowattError)>,
+}
+
+impl Growatt {
+    /// Fetches the given plants concurrently, up to
+    /// [`Growatt::with_plant_concurrency`] at a time, and sums their power and
+    /// energy.
+    ///
+    /// Per-plant failures are collected in [`PortfolioSummary::errors`] rather
+    /// than failing the whole summary.
+    pub async fn get_portfolio_summary(&mut self, plant_ids: &[String]) -> Result<PortfolioSummary> {
+        self.check_login().await?;
+
+        let this = &*self;
+        let results: Vec<_> = stream::iter(plant_ids)
+            .map(|plant_id| async move {
+                let result = async {
+                    let obj = this.fetch_plant_obj(plant_id).await?;
+                    let online = this.fetch_plant_online_at(plant_id, plant_local_now(&obj)).await?;
+                    Ok::<_, GrowattError>((this.plant_data_from_obj(obj)?, online))
+                };
+                (plant_id.clone(), result.await)
+            })
+            .buffer_unordered(this.plant_concurrency)
+            .collect()
+            .await;
+
+        let mut summary = PortfolioSummary::default();
+        for (plant_id, result) in results {
+            match result {
+                Ok((plant_data, online)) => {
+                    if online {
+                        summary.online += 1;
+                    } else {
+                        summary.offline += 1;
+                    }
+                    summary.current_power += plant_data.current_power.unwrap_or(0.0);
+                    summary.today_energy += plant_data.today_energy.unwrap_or(0.0);
+                    summary.total_energy += plant_data.total_energy.unwrap_or(0.0);
+                }
+                Err(e) => {
+                    summary.offline += 1;
+                    summary.errors.push((plant_id, e));
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Builds a [`PortfolioSummary`] for the plants in a group.
+    pub async fn get_group_summary(&mut self, group: &PlantGroup) -> Result<PortfolioSummary> {
+        self.get_portfolio_summary(&group.plant_ids).await
+    }
+
+    /// Applies the same setting to several devices, up to
+    /// [`Growatt::with_plant_concurrency`] at a time.
+    ///
+    /// Returns one result per device in the order given, so a single failing
+    /// device doesn't hide the outcome for the rest of the fleet.
+    pub async fn set_param_bulk(&mut self, device_sns: &[String], setting: SetCommand) -> Result<Vec<(String, Result<bool>)>> {
+        self.check_login().await?;
+
+        let this = &*self;
+        let setting = &setting;
+        let results = stream::iter(device_sns)
+            .map(|device_sn| async move {
+                (device_sn.clone(), this.send_set_command(device_sn, setting).await)
+            })
+            .buffered(this.plant_concurrency)
+            .collect()
+            .await;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_bool, value_as_f64, DeviceType, Growatt, GrowattError, Result};
+
+/// How many devices' history is fetched at once for a per-device breakdown.
+const DEVICE_ENERGY_CONCURRENCY: usize = 4;
+
+/// One record from a device's history, typically a 5-minute sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistorySample {
+    pub time: NaiveDateTime,
+    /// AC output power (W).
+    pub pac: Option<f64>,
+    /// PV input power (W).
+    pub ppv: Option<f64>,
+    /// The full record as returned by the server.
+    pub data: serde_json::Value,
+}
+
+/// The combined day energy of a group of devices, see
+/// [`Growatt::get_group_energy`].
+#[derive(Debug)]
+pub struct GroupEnergy {
+    /// Sum of the day energy of the devices that could be read (kWh).
+    pub total_kwh: f64,
+    /// Devices whose energy couldn't be read, and why. Their energy is
+    /// missing from the total.
+    pub failed: Vec<(String, GrowattError)>,
+}
+
+impl GroupEnergy {
+    /// Whether some devices are missing from the total.
+    pub fn is_partial(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+/// Each device's day energy for a plant, see
+/// [`Growatt::get_daily_energy_all_devices`].
+#[derive(Debug)]
+pub struct DeviceEnergy {
+    /// The day energy of each device that could be read, by serial (kWh).
+    pub energy: Vec<(String, f64)>,
+    /// Devices whose energy couldn't be read, and why.
+    pub failed: Vec<(String, GrowattError)>,
+}
+
+impl DeviceEnergy {
+    /// Whether some devices are missing from the breakdown.
+    pub fn is_partial(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+// One page of history records for a single day
+#[derive(Debug)]
+pub(crate) struct HistoryPage {
+    pub(crate) samples: Vec<HistorySample>,
+    pub(crate) next_start: Option<u32>,
+}
+
+impl HistorySample {
+    /// Energy produced so far that day (kWh), as carried by each record.
+    pub fn energy_today(&self) -> Option<f64> {
+        self.data
+            .get("eacToday")
+            .or_else(|| self.data.get("eToday"))
+            .or_else(|| self.data.get("epvToday"))
+            .and_then(value_as_f64)
+    }
+}
+
+impl HistoryPage {
+    // Records without a parseable time are skipped
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Self {
+        let samples: Vec<HistorySample> = obj
+            .get("datas")
+            .and_then(|datas| datas.as_array())
+            .map(|datas| {
+                datas
+                    .iter()
+                    .filter_map(|record| {
+                        let time = record.get("time")?.as_str()?;
+                        Some(HistorySample {
+                            time: NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").ok()?,
+                            pac: record.get("pac").and_then(value_as_f64),
+                            ppv: record.get("ppv").and_then(value_as_f64),
+                            data: record.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let has_next = obj.get("haveNext").and_then(value_as_bool).unwrap_or(false);
+        let next_start = obj.get("start").and_then(value_as_f64).map(|start| start as u32);
+
+        Self {
+            // A page claiming more data but carrying none would loop forever
+            next_start: if has_next && !samples.is_empty() { next_start } else { None },
+            samples,
+        }
+    }
+}
+
+// State threaded through the history stream
+struct HistoryCursor<'a> {
+    client: &'a mut Growatt,
+    device_sn: &'a str,
+    day: NaiveDate,
+    end: NaiveDate,
+    page_start: u32,
+    // Pages fetched for the current day
+    day_pages: usize,
+    pending: VecDeque<HistorySample>,
+    session_checked: bool,
+    done: bool,
+}
+
+impl Growatt {
+    /// Streams a device's history between two dates (inclusive).
+    ///
+    /// History is fetched one day (and one page) at a time and yielded sample
+    /// by sample, so memory stays bounded however long the range is. The
+    /// stream ends after the first error; an inverted range yields a single
+    /// [`GrowattError::InvalidInput`].
+    pub fn stream_device_history<'a>(
+        &'a mut self,
+        device_sn: &'a str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl Stream<Item = Result<HistorySample>> + 'a {
+        let cursor = HistoryCursor {
+            client: self,
+            device_sn,
+            day: start,
+            end,
+            page_start: 0,
+            day_pages: 0,
+            pending: VecDeque::new(),
+            session_checked: false,
+            done: false,
+        };
+
+        stream::unfold(cursor, |mut cursor| async move {
+            loop {
+                if let Some(sample) = cursor.pending.pop_front() {
+                    return Some((Ok(sample), cursor));
+                }
+                if cursor.done {
+                    return None;
+                }
+                if cursor.day > cursor.end {
+                    // Nothing fetched yet means the range itself was inverted
+                    if cursor.session_checked {
+                        return None;
+                    }
+                    cursor.done = true;
+                    let error = GrowattError::InvalidInput(format!(
+                        "Start date {} is after end date {}",
+                        cursor.day, cursor.end
+                    ));
+                    return Some((Err(error), cursor));
+                }
+
+                if let Err(e) = cursor.client.check_page_limit(cursor.day_pages) {
+                    cursor.done = true;
+                    return Some((Err(e), cursor));
+                }
+
+                if !cursor.session_checked {
+                    if let Err(e) = cursor.client.check_login().await {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                    cursor.session_checked = true;
+                }
+
+                match cursor.client.fetch_history_page(cursor.device_sn, cursor.day, cursor.page_start).await {
+                    Ok(page) => {
+                        cursor.pending.extend(page.samples);
+                        cursor.day_pages += 1;
+                        match page.next_start {
+                            Some(next_start) => cursor.page_start = next_start,
+                            None => match cursor.day.succ_opt() {
+                                Some(next_day) => {
+                                    cursor.day = next_day;
+                                    cursor.page_start = 0;
+                                    cursor.day_pages = 0;
+                                }
+                                None => cursor.done = true,
+                            },
+                        }
+                    }
+                    Err(e) => {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Fetches each device's energy for a day (kWh), concurrently.
+    ///
+    /// Discovers the plant's devices and reads the day's total from each one's
+    /// history, from the endpoint for its device type. Devices that fail are
+    /// listed apart, so a single failing device doesn't hide the others; a
+    /// device with no records that day reports 0.
+    pub async fn get_daily_energy_all_devices(&mut self, plant_id: &str, date: NaiveDate) -> Result<DeviceEnergy> {
+        self.check_login().await?;
+
+        // Devices without a type are read as plain inverters
+        let devices: Vec<(String, DeviceType)> = self
+            .fetch_plant_devices(plant_id)
+            .await?
+            .iter()
+            .filter_map(|device| {
+                let device_sn = device.get("deviceSn")?.as_str()?.to_owned();
+                let device_type = device
+                    .get("deviceType")
+                    .and_then(|device_type| device_type.as_str())
+                    .map_or(DeviceType::Inverter, DeviceType::from_name);
+                Some((device_sn, device_type))
+            })
+            .collect();
+
+        let this = &*self;
+        let results: Vec<(String, Result<f64>)> = stream::iter(devices)
+            .map(|(device_sn, device_type)| async move {
+                let energy = this.fetch_day_energy(&device_type, &device_sn, date).await;
+                (device_sn, energy)
+            })
+            .buffered(DEVICE_ENERGY_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut breakdown = DeviceEnergy { energy: Vec::new(), failed: Vec::new() };
+        for (device_sn, energy) in results {
+            match energy {
+                Ok(kwh) => breakdown.energy.push((device_sn, kwh)),
+                Err(e) => breakdown.failed.push((device_sn, e)),
+            }
+        }
+        Ok(breakdown)
+    }
+
+    /// Sums the day energy (kWh) of an arbitrary group of devices, e.g. the
+    /// inverters of one sub-array, fetched concurrently.
+    ///
+    /// Devices that fail are listed in the result rather than failing the
+    /// whole group; only if every device fails is the first error returned.
+    pub async fn get_group_energy(&mut self, device_sns: &[String], date: NaiveDate) -> Result<GroupEnergy> {
+        self.check_login().await?;
+
+        let this = &*self;
+        let results: Vec<(String, Result<f64>)> = stream::iter(device_sns)
+            .map(|device_sn| async move {
+                let energy = this.fetch_day_energy(&DeviceType::Inverter, device_sn, date).await;
+                (device_sn.clone(), energy)
+            })
+            .buffered(DEVICE_ENERGY_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut group = GroupEnergy { total_kwh: 0.0, failed: Vec::new() };
+        let mut any_succeeded = device_sns.is_empty();
+        for (device_sn, energy) in results {
+            match energy {
+                Ok(kwh) => {
+                    group.total_kwh += kwh;
+                    any_succeeded = true;
+                }
+                Err(e) => group.failed.push((device_sn, e)),
+            }
+        }
+
+        if !any_succeeded {
+            return Err(group.failed.swap_remove(0).1);
+        }
+        Ok(group)
+    }
+
+    // The running energy total peaks with the day's last record, and the
+    // portal lists a day's history newest first, so the first page carries it
+    async fn fetch_day_energy(&self, device_type: &DeviceType, device_sn: &str, day: NaiveDate) -> Result<f64> {
+        let page = self.fetch_device_history_page(device_type, device_sn, day, 0).await?;
+        Ok(page
+            .samples
+            .iter()
+            .filter(|sample| sample.time.date() == day)
+            .filter_map(HistorySample::energy_today)
+            .fold(0.0, f64::max))
+    }
+
+    async fn fetch_history_page(&self, device_sn: &str, day: NaiveDate, start: u32) -> Result<HistoryPage> {
+        self.fetch_device_history_page(&DeviceType::Inverter, device_sn, day, start).await
+    }
+
+    async fn fetch_device_history_page(
+        &self,
+        device_type: &DeviceType,
+        device_sn: &str,
+        day: NaiveDate,
+        start: u32,
+    ) -> Result<HistoryPage> {
+        let (path, sn_key) = history_endpoint(device_type).ok_or_else(|| {
+            GrowattError::InvalidInput(format!("No history endpoint for device type {:?}", device_type))
+        })?;
+        let day = day.format("%Y-%m-%d").to_string();
+        let start = start.to_string();
+
+        let form = [
+            (sn_key, device_sn),
+            ("startDate", &day),
+            ("endDate", &day),
+            ("start", &start),
+        ];
+
+        let request = self.client
+            .post(self.endpoint(path))
+            .form(&form);
+
+        let json_response = self.request_json(request).await?;
+
+        match json_response.get("obj") {
+            // A day without data comes back as a null obj
+            Some(obj) if obj.is_null() => Ok(HistoryPage { samples: Vec::new(), next_start: None }),
+            Some(obj) => Ok(HistoryPage::from_value(obj)),
+            None => Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            )),
+        }
+    }
+}
+
+// The history endpoint of a device family, and the form key for its serial
+fn history_endpoint(device_type: &DeviceType) -> Option<(&'static str, &'static str)> {
+    match device_type {
+        DeviceType::Inverter => Some(("/device/getInverterHistory", "inverterSn")),
+        DeviceType::Max => Some(("/device/getMAXHistory", "maxSn")),
+        DeviceType::Tlx => Some(("/device/getTLXHistory", "tlxSn")),
+        DeviceType::Storage => Some(("/device/getStorageHistory", "storageSn")),
+        DeviceType::Mix | DeviceType::Sph => Some(("/device/getMIXHistory", "mixSn")),
+        DeviceType::Spa => Some(("/device/getSPAHistory", "spaSn")),
+        DeviceType::Other(_) => None,
+    }
+}
@@ -1,12 +1,79 @@
 use chrono::prelude::*;
+use futures::StreamExt;
 use md5::{Digest, Md5};
-use reqwest::{Client, cookie::Jar};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use thiserror::Error;
 use std::env;
 use dotenv::dotenv;
 
+mod alarms;
+mod api;
+mod auth;
+mod battery;
+mod builder;
+#[cfg(not(feature = "minimal"))]
+mod cache;
+mod chart;
+mod control;
+mod devices;
+mod events;
+#[cfg(feature = "csv")]
+mod export;
+mod faults;
+mod history;
+mod meter;
+mod metrics;
+mod mix;
+mod mppt;
+mod openapi;
+mod plant;
+mod rate_limit;
+mod retry;
+mod serde_helpers;
+mod session;
+mod storage;
+mod tlx;
+mod units;
+mod snapshot;
+mod watch;
+#[cfg(not(feature = "minimal"))]
+mod fleet;
+mod weather;
+
+pub use alarms::{AlarmRule, Comparison};
+pub use api::GrowattApi;
+#[cfg(feature = "mock")]
+pub use api::MockGrowattApi;
+pub use auth::{Credentials, LoginResult, Role};
+pub use builder::GrowattBuilder;
+pub use battery::{BatteryCells, CellData};
+pub use chart::{Anchor, ChartDay, ChartMonth, ChartYear, EnergyChart, GapFill, PowerSample};
+pub use control::{ChargeSchedule, MixSettings, MixSlots, SetCommand, TimePeriod};
+#[cfg(not(feature = "minimal"))]
+pub use fleet::{PlantGroup, PortfolioSummary};
+pub use devices::{resolve_device_state, Device, DeviceList, DevicePage, DevicePageStream, DeviceState, DeviceType, DevicesByType};
+pub use events::DeviceEvent;
+#[cfg(feature = "csv")]
+pub use export::energy_chart_to_csv;
+pub use faults::{FaultLog, FaultLogPage, FaultSeverity};
+pub use history::{DeviceEnergy, GroupEnergy, HistorySample};
+pub use meter::{MeterPhases, PhaseReading};
+pub use metrics::{ErrorKind, RequestMetrics};
+pub use mix::{EnvironmentalImpact, GridExchange, MixDevice, MixRunState, MixStatusData, MixTotalData, SphTotalData};
+pub use mppt::{DeviceInfo, Mppt};
+pub use openapi::ApiMode;
+pub use plant::{Address, PanelConfig, PlantTotal, Tariff};
+pub use retry::{CircuitBreaker, RetryBudget};
+pub use session::CookieJar;
+pub use snapshot::{BatteryState, Change, FaultCode, PlantSnapshot};
+pub use storage::{StorageStatusData, StorageTotalData};
+pub use tlx::{TlxStatusData, TlxTotalData};
+pub use units::{AccountUnits, EnergyUnit, PowerUnit, TemperatureUnit};
+pub use watch::PollSchedule;
+pub use weather::{EnvSensor, ForecastEntry, Weather};
+
 // Include test modules
 #[cfg(test)]
 mod tests;
@@ -27,10 +94,113 @@ pub enum GrowattError {
 
     #[error("Not logged in")]
     NotLoggedIn,
+
+    #[error("Session expired: the server returned its login page")]
+    SessionExpired,
+
+    #[error("Response exceeded the maximum size of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+
+    #[error("The device does not report this data")]
+    NoData,
+
+    #[error("Plant {plant_id} belongs to another account")]
+    Forbidden { plant_id: String },
+
+    #[error("Stopped after {limit} pages; the server may be misreporting the page count")]
+    PageLimitExceeded { limit: usize },
+
+    #[error("Circuit breaker open: not calling the server after repeated failures")]
+    CircuitOpen,
+
+    #[error("Setting {param_type} was acknowledged but not applied")]
+    NotApplied { param_type: String },
+
+    /// A write made of several settings stopped partway, e.g. after the
+    /// battery-first half of a charge schedule. `source` is the error of the
+    /// failed step, or `None` if the server rejected it.
+    #[error("Only the {applied} settings were applied; writing the {failed} settings failed")]
+    PartiallyApplied {
+        applied: String,
+        failed: String,
+        #[source]
+        source: Option<Box<GrowattError>>,
+    },
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// An argument was rejected before anything was sent, e.g. a percentage
+    /// over 100 or a date range that ends before it starts.
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The server wants a captcha solved before accepting the login, see
+    /// [`Growatt::login_with_captcha`]. Carries the captcha image URL or
+    /// token when the server sends one.
+    #[error("Login requires a captcha")]
+    CaptchaRequired { captcha: Option<String> },
+
+    #[error("Login rejected: wrong username or password")]
+    InvalidCredentials,
+
+    #[error("Login rejected: the account is locked after too many failed attempts")]
+    AccountLocked,
+
+    #[error("Login rejected: the account is frozen")]
+    AccountFrozen,
+
+    #[error("Login rejected: the account does not exist")]
+    AccountNotFound,
 }
 
 pub type Result<T> = std::result::Result<T, GrowattError>;
 
+// Session state of a login run on a copy of a client, see
+// `Growatt::recover_expired_session`
+#[derive(Debug, Clone)]
+struct RecoveredSession {
+    generation: u64,
+    expiry: Option<DateTime<Utc>>,
+    token: Option<String>,
+    account_units: Option<AccountUnits>,
+}
+
+/// Default cap on response bodies: far above any real Growatt payload, but
+/// finite so a runaway response can't exhaust memory.
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default cap on the pages walked by one "fetch all" call.
+pub const DEFAULT_MAX_PAGES: usize = 500;
+
+/// User-Agent sent with every request unless overridden with
+/// [`Growatt::with_user_agent`]. The portal is built for browsers, so a
+/// browser-like value gets the same responses the web UI does.
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36";
+
+/// The web portal the client talks to unless pointed elsewhere.
+const DEFAULT_BASE_URL: &str = "https://server.growatt.com";
+
+/// Default number of plants [`Growatt::get_all_plant_data`] and
+/// [`Growatt::get_portfolio_summary`] fetch at once.
+pub const DEFAULT_PLANT_CONCURRENCY: usize = 8;
+
+/// Default time allowed for each request, see [`Growatt::with_timeout`].
+pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Per-call settings, applied with [`Growatt::with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Overrides the client's default timeout for each request of the call.
+    pub timeout: Option<std::time::Duration>,
+}
+
+/// Login `result` code returned when the account has not accepted the terms of use.
+const LOGIN_RESULT_TERMS_REQUIRED: i64 = 3;
+
 // Define structs for plant data
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Plant {
@@ -40,62 +210,385 @@ pub struct Plant {
     pub plant_name: String,
     #[serde(rename = "plantAddress", default)]
     pub plant_address: Option<String>,
-    #[serde(rename = "plantPower", default)]
+    #[serde(rename = "plantPower", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
     pub plant_watts: Option<f64>,
-    #[serde(rename = "isShare", default)]
+    #[serde(rename = "isShare", default, deserialize_with = "serde_helpers::deserialize_lenient_bool")]
     pub is_share: Option<bool>,
+    /// Account of the owner who shared the plant, for plants shared with you.
+    #[serde(rename = "shareAccount", default)]
+    pub shared_by: Option<String>,
+}
+
+impl Plant {
+    /// Whether the plant was shared with this account rather than owned by it.
+    /// Some server versions omit `isShare` but still name the sharing owner.
+    pub fn is_shared(&self) -> bool {
+        self.is_share.unwrap_or(false) || self.shared_by.is_some()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlantList(pub Vec<Plant>);
 
-#[derive(Debug, Serialize, Deserialize)]
+impl PlantList {
+    /// Plants other accounts have shared with this one.
+    pub fn shared(&self) -> Vec<&Plant> {
+        self.0.iter().filter(|plant| plant.is_shared()).collect()
+    }
+
+    /// Plants owned by this account.
+    pub fn owned(&self) -> Vec<&Plant> {
+        self.0.iter().filter(|plant| !plant.is_shared()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlantData {
     #[serde(rename = "plantName")]
     pub plant_name: Option<String>,
     #[serde(rename = "plantId")]
     pub plant_id: Option<String>,
-    #[serde(rename = "capacity")]
+    #[serde(rename = "capacity", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
     pub capacity: Option<f64>,
-    #[serde(rename = "todayEnergy")]
+    #[serde(rename = "todayEnergy", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
     pub today_energy: Option<f64>,
-    #[serde(rename = "totalEnergy")]
+    #[serde(rename = "totalEnergy", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
     pub total_energy: Option<f64>,
-    #[serde(rename = "currentPower")]
+    /// Current output power. Normalized to watts by [`Growatt::get_plant`], as
+    /// are the other powers; see [`PlantData::normalize_units`].
+    #[serde(rename = "currentPower", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
     pub current_power: Option<f64>,
+    /// Current power with its unit, e.g. `"4.5kW"` or `"4500W"`.
+    #[serde(rename = "currentPowerStr", default)]
+    pub current_power_str: Option<String>,
+    /// Rated AC output of the plant's inverters, from the nameplate data.
+    #[serde(rename = "ratedPower", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
+    pub rated_power: Option<f64>,
+    /// DC nameplate power of the plant's panels.
+    #[serde(rename = "nominalPower", default, deserialize_with = "serde_helpers::deserialize_lenient_f64")]
+    pub nominal_power: Option<f64>,
     // Add more fields as needed based on the actual API response
 }
 
+/// Converts a raw plant data result, or a full response with the data under `obj`.
+impl TryFrom<&serde_json::Value> for PlantData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        serde_helpers::from_result_value(value)
+    }
+}
+
+impl PlantData {
+    /// Returns the current power in watts.
+    ///
+    /// Depending on the endpoint `currentPower` is in W or kW with nothing to
+    /// tell which, so the unit suffix of `currentPowerStr` decides when present.
+    /// Without it the value is assumed to be in watts already.
+    pub fn current_power_watts(&self) -> Option<f64> {
+        let with_unit = self.current_power_str.as_deref().map(str::trim).and_then(|text| {
+            let lower = text.to_ascii_lowercase();
+            if let Some(value) = lower.strip_suffix("kw") {
+                value.trim().parse::<f64>().ok().map(|kw| kw * 1000.0)
+            } else if let Some(value) = lower.strip_suffix('w') {
+                value.trim().parse::<f64>().ok()
+            } else {
+                None
+            }
+        });
+
+        with_unit.or(self.current_power)
+    }
+
+    /// Rewrites every power in watts, so they can be compared with each other.
+    ///
+    /// `current_power` follows the unit suffix of `currentPowerStr` when present
+    /// (see [`PlantData::current_power_watts`]); otherwise it is, like the rated
+    /// and nominal power and the capacity, converted from the account's units.
+    pub fn normalize_units(&mut self, units: &AccountUnits) {
+        let to_watts = |power: Option<f64>| power.map(|power| units.to_watts(power));
+
+        self.current_power = match self.current_power_str {
+            Some(_) => self.current_power_watts(),
+            None => to_watts(self.current_power),
+        };
+        self.capacity = to_watts(self.capacity);
+        self.rated_power = to_watts(self.rated_power);
+        self.nominal_power = to_watts(self.nominal_power);
+    }
+
+    /// Fraction of the rated AC power at which output is considered clipped.
+    /// Inverters throttle slightly below their nameplate, so an exact match is rare.
+    pub const CLIPPING_THRESHOLD: f64 = 0.98;
+
+    /// Returns whether the plant is currently clipping, i.e. its output is pinned
+    /// at the inverter's rated AC power.
+    ///
+    /// Returns `None` when either the current power or the rated power is missing.
+    /// The two are compared as they are, so they must be in the same unit, as
+    /// they are after [`PlantData::normalize_units`] (see [`Growatt::get_plant`]).
+    pub fn is_clipping(&self) -> Option<bool> {
+        let current = self.current_power?;
+        let rated = self.rated_power.filter(|rated| *rated > 0.0)?;
+        Some(current >= rated * Self::CLIPPING_THRESHOLD)
+    }
+
+    /// Fraction of the nominal power below which the plant is assumed to be
+    /// idle for the night rather than underperforming.
+    pub const NIGHT_POWER_THRESHOLD: f64 = 0.01;
+
+    /// Returns whether the plant produces less than `expected_ratio` of its
+    /// nominal (DC nameplate) power.
+    ///
+    /// Returns `None` when either value is missing, and at night (output below
+    /// [`PlantData::NIGHT_POWER_THRESHOLD`] of nominal) to avoid false alarms.
+    /// As with [`PlantData::is_clipping`], both powers must be in the same unit.
+    pub fn underperforming(&self, expected_ratio: f64) -> Option<bool> {
+        let current = self.current_power?;
+        let nominal = self.nominal_power.filter(|nominal| *nominal > 0.0)?;
+
+        let ratio = current / nominal;
+        if ratio < Self::NIGHT_POWER_THRESHOLD {
+            return None;
+        }
+        Some(ratio < expected_ratio)
+    }
+}
+
+// Growatt encodes most numbers as strings, so accept both forms
+fn value_as_f64(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        // "NaN" and "inf" parse as floats but are never real readings
+        serde_json::Value::String(s) => s.trim().parse().ok().filter(|n: &f64| n.is_finite()),
+        _ => None,
+    }
+}
+
+// An expired session often gets an empty body or obj instead of an error
+fn is_empty_value(value: &serde_json::Value) -> bool {
+    value.is_null()
+        || value.as_object().is_some_and(|obj| obj.is_empty())
+        || value.as_array().is_some_and(|array| array.is_empty())
+}
+
+fn empty_response() -> GrowattError {
+    GrowattError::InvalidResponse("Empty response. Please ensure you are logged in.".to_string())
+}
+
+// Takes the response's non-empty obj
+pub(crate) fn response_obj(mut json_response: serde_json::Value) -> Result<serde_json::Value> {
+    match json_response.get_mut("obj").map(serde_json::Value::take) {
+        Some(obj) if is_empty_value(&obj) => Err(empty_response()),
+        Some(obj) => Ok(obj),
+        None => Err(GrowattError::InvalidResponse(
+            "Invalid response structure".to_string(),
+        )),
+    }
+}
+
+// Result code the portal sets for a plant that belongs to another account
+const RESULT_NO_PERMISSION: i64 = 10011;
+
+// Fallback for responses that only say so in `msg`. The message is localized,
+// so this only catches accounts set to English.
+const NO_PERMISSION_MESSAGES: [&str; 3] = ["permission", "authority", "not belong"];
+
+// Requests for another account's plant come back as a failed result with the
+// no-permission code, rather than the empty obj of an expired session
+fn check_plant_access(json_response: &serde_json::Value, plant_id: &str) -> Result<()> {
+    let result = json_response.get("result").and_then(value_as_f64).map(|result| result as i64);
+    let message = json_response
+        .get("msg")
+        .and_then(|msg| msg.as_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let failed = result.is_some_and(|result| result != 1);
+    if result == Some(RESULT_NO_PERMISSION) || (failed && NO_PERMISSION_MESSAGES.iter().any(|text| message.contains(text))) {
+        return Err(GrowattError::Forbidden { plant_id: plant_id.to_string() });
+    }
+    Ok(())
+}
+
+/// Parses a response body as JSON.
+///
+/// Once a session is gone Growatt answers data calls with its HTML login page
+/// and a 200 status, so HTML bodies are reported as [`GrowattError::SessionExpired`]
+/// instead of a JSON parse error. A JSON body is still accepted when the server
+/// mislabels it as `text/html`, which some endpoints do.
+fn parse_json_body(content_type: Option<&str>, body: &str) -> Result<serde_json::Value> {
+    let trimmed = body.trim_start();
+    let prefix = trimmed.get(..9).unwrap_or(trimmed).to_ascii_lowercase();
+    if prefix.starts_with("<!doctype") || prefix.starts_with("<html") {
+        return Err(GrowattError::SessionExpired);
+    }
+
+    let is_html = content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("text/html"));
+    match serde_json::from_str(body) {
+        Ok(json) => Ok(json),
+        Err(_) if is_html => Err(GrowattError::SessionExpired),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Flags come back as booleans, 0/1 or their string forms
+fn value_as_bool(value: &serde_json::Value) -> Option<bool> {
+    match value {
+        serde_json::Value::Bool(b) => Some(*b),
+        serde_json::Value::Number(n) => n.as_i64().map(|n| n != 0),
+        serde_json::Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_proxy(url: &str) -> Result<reqwest::Proxy> {
+    reqwest::Proxy::all(url).map_err(|e| GrowattError::InvalidConfig(format!("invalid proxy URL {:?}: {}", url, e)))
+}
+
+// Settings the HTTP client is built with
+#[derive(Debug, Clone, Default)]
+struct ConnectionOptions {
+    // The caller supplied the client, so there is nothing to rebuild
+    external: bool,
+    http2_prior_knowledge: bool,
+    http2_keep_alive_interval: Option<std::time::Duration>,
+    tcp_keepalive: Option<std::time::Duration>,
+    proxy: Option<reqwest::Proxy>,
+    user_agent: Option<reqwest::header::HeaderValue>,
+}
+
+impl ConnectionOptions {
+    fn build_client(&self, cookies: &Arc<session::CookieJar>) -> Client {
+        let user_agent = self
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| reqwest::header::HeaderValue::from_static(DEFAULT_USER_AGENT));
+        let mut builder = Client::builder()
+            .cookie_provider(Arc::clone(cookies))
+            .user_agent(user_agent)
+            .http2_keep_alive_interval(self.http2_keep_alive_interval)
+            .tcp_keepalive(self.tcp_keepalive);
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        builder.build().unwrap()
+    }
+}
+
+#[derive(Clone)]
 pub struct Growatt {
     base_url: String,
     client: Client,
+    cookies: Arc<session::CookieJar>,
+    connection: ConnectionOptions,
+    // Set once the shared session has been logged out, see `execute`
+    session_gate: Arc<tokio::sync::RwLock<bool>>,
+    // Counts logins, so concurrent calls recovering an expired session can
+    // tell whether another one already logged in, see `recover_expired_session`
+    session_generation: Arc<std::sync::atomic::AtomicU64>,
+    relogin_lock: Arc<tokio::sync::Mutex<()>>,
+    // Session state of a login `recover_expired_session` ran on a copy of the
+    // client, for the copies that didn't run it to pick up
+    recovered_session: Arc<std::sync::Mutex<Option<RecoveredSession>>>,
+    // The session generation of this client's own latest login
+    login_generation: u64,
     username: Option<String>,
     password: Option<String>,
+    // Accounts to fail over between, see `with_credentials`
+    credentials: Vec<Credentials>,
     is_logged_in: bool,
     session_expiry: Option<DateTime<Utc>>,
     session_duration: chrono::Duration,
     token: Option<String>,  // Add token field
+    read_pact: bool,
+    online_threshold: chrono::Duration,
+    retry_policy: Option<retry::RetryPolicy>,
+    retry_budget: Option<Arc<RetryBudget>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    max_response_size: usize,
+    max_pages: usize,
+    plant_concurrency: usize,
+    timeout: std::time::Duration,
+    api_token: Option<String>,
+    // Calls the v1 REST API, see `api_mode`
+    openapi: bool,
+    on_request_complete: Option<Arc<metrics::RequestHook>>,
+    #[cfg(not(feature = "minimal"))]
+    response_inspector: Option<Arc<metrics::ResponseInspector>>,
+    // Headers of the latest response, when capture is enabled
+    #[cfg(not(feature = "minimal"))]
+    last_headers: Option<Arc<std::sync::Mutex<Option<reqwest::header::HeaderMap>>>>,
+    #[cfg(not(feature = "minimal"))]
+    plant_cache: Option<Arc<cache::PlantDataCache>>,
+    account_units: Option<AccountUnits>,
+    si_units: bool,
 }
 
 impl Growatt {
+    /// Creates a client with the default configuration, see [`GrowattBuilder`]
+    /// to configure one with validation.
     pub fn new() -> Self {
-        // Create a client with cookie storage
-        let jar = Arc::new(Jar::default());
-        let client = Client::builder()
-            .cookie_provider(Arc::clone(&jar))
+        GrowattBuilder::default()
             .build()
-            .unwrap();
+            .expect("the default configuration is valid")
+    }
+
+    // The default client, before any configuration is applied
+    fn unconfigured() -> Self {
+        // Create a client with cookie storage
+        let cookies = Arc::new(session::CookieJar::default());
+        let connection = ConnectionOptions::default();
+        let client = connection.build_client(&cookies);
 
         Self {
-            base_url: "https://server.growatt.com".to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             client,
+            cookies,
+            connection,
+            session_gate: Arc::default(),
+            session_generation: Arc::default(),
+            relogin_lock: Arc::default(),
+            recovered_session: Arc::default(),
+            login_generation: 0,
             username: None,
             password: None,
+            credentials: Vec::new(),
             is_logged_in: false,
             session_expiry: None,
             // Default session duration of 30 minutes
             session_duration: chrono::Duration::minutes(30),
             token: None,  // Initialize token as None
+            read_pact: true,
+            // Devices report every 5 minutes, allow a few missed updates
+            online_threshold: chrono::Duration::minutes(20),
+            retry_policy: None,
+            retry_budget: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            max_pages: DEFAULT_MAX_PAGES,
+            plant_concurrency: DEFAULT_PLANT_CONCURRENCY,
+            timeout: DEFAULT_TIMEOUT,
+            api_token: None,
+            openapi: false,
+            on_request_complete: None,
+            #[cfg(not(feature = "minimal"))]
+            response_inspector: None,
+            #[cfg(not(feature = "minimal"))]
+            last_headers: None,
+            #[cfg(not(feature = "minimal"))]
+            plant_cache: None,
+            account_units: None,
+            si_units: false,
         }
     }
     
@@ -106,6 +599,7 @@ impl Growatt {
     /// - GROWATT_PASSWORD: The password for Growatt API
     /// - GROWATT_BASE_URL: (Optional) Alternative base URL (defaults to standard URL if not set)
     /// - GROWATT_SESSION_DURATION: (Optional) Session duration in minutes (defaults to 30 minutes if not set)
+    /// - GROWATT_API_TOKEN: (Optional) Token for the v1 REST API, see [`Growatt::with_api_token`]
     /// 
     /// You can set these variables in a `.env` file in the project directory.
     pub fn from_env() -> Self {
@@ -135,108 +629,847 @@ impl Growatt {
             }
         }
         
+        if let Ok(token) = env::var("GROWATT_API_TOKEN") {
+            client = client.with_api_token(&token);
+        }
+
+        // The Growatt servers are reached over HTTPS, so its proxy wins. An
+        // invalid proxy URL is ignored rather than failing the whole setup.
+        let proxy = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+            .iter()
+            .find_map(|key| env::var(key).ok().filter(|url| !url.is_empty()));
+        if let Some(url) = proxy {
+            client = match parse_proxy(&url) {
+                Ok(proxy) => client.set_proxy(proxy),
+                Err(e) => {
+                    tracing::warn!("Ignoring proxy from the environment: {}", e);
+                    client
+                }
+            };
+        }
+
+        client
+    }
+
+    /// Creates a client from the environment like [`Growatt::from_env`] and
+    /// logs in with the credentials found there.
+    ///
+    /// Returns [`GrowattError::NotLoggedIn`] without contacting the server if
+    /// `GROWATT_USERNAME` or `GROWATT_PASSWORD` is missing. A rejected login
+    /// fails with the same errors as [`Growatt::login`], e.g.
+    /// [`GrowattError::AccountNotFound`] for a username without an account.
+    pub async fn connect_from_env() -> Result<Self> {
+        Self::from_env().connect().await
+    }
+
+    // Logs in with the stored credentials, failing if there are none
+    async fn connect(mut self) -> Result<Self> {
+        let (Some(username), Some(password)) = (self.username.clone(), self.password.clone()) else {
+            return Err(GrowattError::NotLoggedIn);
+        };
+
+        self.login(&username, &password).await?;
+        Ok(self)
+    }
+
+    /// Creates a client pointed at `base_url` that considers itself logged in,
+    /// without contacting the server. Meant for testing code that uses a
+    /// client against a mock server.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn test_logged_in(base_url: &str) -> Self {
+        let mut client = Self::new();
+        client.base_url = base_url.to_string();
+        client.is_logged_in = true;
+        client.session_expiry = Some(Utc::now() + chrono::Duration::days(365 * 100));
         client
     }
 
+    /// Points the client at another server. The URL may include a path prefix,
+    /// e.g. `https://gw.corp/growatt` for a gateway, which is kept in front of
+    /// every endpoint path.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Switches to the v1 REST API on `openapi.growatt.com`, see
+    /// [`Growatt::with_api_token`], which also needs to be called. Without a
+    /// token the v1 calls fail with [`GrowattError::InvalidConfig`].
     pub fn with_alternate_url(mut self) -> Self {
-        self.base_url = "https://openapi.growatt.com".to_string();
+        self.base_url = openapi::OPENAPI_BASE_URL.to_string();
+        self.openapi = true;
+        self
+    }
+
+    /// Sets the API token for the v1 REST API and switches to it. Requests go
+    /// to `openapi.growatt.com` unless another base URL was set, e.g. for a
+    /// gateway.
+    ///
+    /// With a token set, [`Growatt::get_plants`], [`Growatt::get_plant`] and
+    /// [`Growatt::get_device_list`] call the `/v1/...` endpoints with the token
+    /// in a header instead of logging in. The v1 API has no equivalent for the
+    /// other methods, which call portal endpoints the v1 host doesn't serve;
+    /// use a separate portal client for those.
+    pub fn with_api_token(mut self, token: &str) -> Self {
+        self.api_token = Some(token.to_string());
+        self.openapi = true;
+        if self.base_url == DEFAULT_BASE_URL {
+            self.base_url = openapi::OPENAPI_BASE_URL.to_string();
+        }
         self
     }
 
+    /// Speaks HTTP/2 from the first request instead of negotiating it.
+    ///
+    /// Over HTTPS the client already negotiates HTTP/2 when the server offers
+    /// it, so this only matters for servers that support HTTP/2 without
+    /// advertising it. Requests fail against servers that only speak HTTP/1.1.
+    pub fn with_http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.connection.http2_prior_knowledge = enabled;
+        self.rebuild_client()
+    }
+
+    /// Sends HTTP/2 pings at this interval to keep idle connections open.
+    pub fn with_http2_keep_alive_interval(mut self, interval: std::time::Duration) -> Self {
+        self.connection.http2_keep_alive_interval = Some(interval);
+        self.rebuild_client()
+    }
+
+    /// Enables TCP keepalive on connections, so pooled connections survive
+    /// between polls instead of being set up again. Works with HTTP/1.1 too.
+    pub fn with_tcp_keepalive(mut self, interval: std::time::Duration) -> Self {
+        self.connection.tcp_keepalive = Some(interval);
+        self.rebuild_client()
+    }
+
+    /// Sends all requests through an HTTP, HTTPS or SOCKS proxy, e.g.
+    /// `http://proxy.corp:3128`. Fails with [`GrowattError::InvalidConfig`] if
+    /// the URL isn't a valid proxy URL.
+    pub fn with_proxy(self, url: &str) -> Result<Self> {
+        let proxy = parse_proxy(url)?;
+        Ok(self.set_proxy(proxy))
+    }
+
+    /// Like [`Growatt::with_proxy`], for a proxy that requires basic
+    /// authentication.
+    pub fn with_proxy_auth(self, url: &str, username: &str, password: &str) -> Result<Self> {
+        let proxy = parse_proxy(url)?.basic_auth(username, password);
+        Ok(self.set_proxy(proxy))
+    }
+
+    /// Sets the User-Agent sent with every request. Defaults to
+    /// [`DEFAULT_USER_AGENT`]. A value that isn't a valid header is ignored.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        match reqwest::header::HeaderValue::from_str(user_agent) {
+            Ok(user_agent) => {
+                self.connection.user_agent = Some(user_agent);
+                self.rebuild_client()
+            }
+            Err(_) => {
+                tracing::warn!("Ignoring invalid User-Agent {:?}", user_agent);
+                self
+            }
+        }
+    }
+
+    fn set_proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.connection.proxy = Some(proxy);
+        self.rebuild_client()
+    }
+
+    // The cookie jar is kept, so an existing session survives
+    fn rebuild_client(mut self) -> Self {
+        if self.connection.external {
+            tracing::warn!("Connection settings don't apply to a client set with with_client");
+            return self;
+        }
+        self.client = self.connection.build_client(&self.cookies);
+        self
+    }
+
+    /// Uses a pre-built HTTP client instead of one of the client's own, e.g.
+    /// to share a connection pool or TLS setup with the rest of an application.
+    ///
+    /// The session lives in the client's cookies, so build it with
+    /// `.cookie_provider(growatt.cookie_jar())`. A client with reqwest's own
+    /// cookie store works too, but its cookies survive [`Growatt::reconnect`]
+    /// and aren't included by [`Growatt::save_session`].
+    ///
+    /// The connection settings (proxy, user agent, keepalive, HTTP/2) are the
+    /// supplied client's; setting them on this client afterwards has no
+    /// effect. Timeouts, retries and rate limits still apply.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self.connection.external = true;
+        self
+    }
+
+    /// The cookie jar holding the session, for building a client to pass to
+    /// [`Growatt::with_client`].
+    pub fn cookie_jar(&self) -> Arc<session::CookieJar> {
+        Arc::clone(&self.cookies)
+    }
+
+    /// Sets the time allowed for each request, login included. Defaults to
+    /// [`DEFAULT_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns a handle for a single call with its own settings, e.g. a short
+    /// timeout for a status poll. The handle shares the session, cookies and
+    /// everything else with this client.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        let mut client = self.clone();
+        if let Some(timeout) = options.timeout {
+            client.timeout = timeout;
+        }
+        client
+    }
+
     pub fn with_session_duration(mut self, minutes: i64) -> Self {
         self.session_duration = chrono::Duration::minutes(minutes);
         self
     }
 
+    /// Sets how recently a device must have reported to count as online in
+    /// [`Growatt::is_plant_online`]. Defaults to 20 minutes.
+    pub fn with_online_threshold(mut self, minutes: i64) -> Self {
+        self.online_threshold = chrono::Duration::minutes(minutes);
+        self
+    }
+
+    /// Retries requests that time out or get a 5xx response, up to
+    /// `max_attempts` attempts in all, with exponential backoff from
+    /// `base_delay` plus jitter. Other failures are returned straight away,
+    /// and retrying stops after a minute so callers' own timeouts still hold.
+    /// Combine with [`Growatt::with_retry_budget`] to cap retries client-wide.
+    pub fn with_retry(mut self, max_attempts: u32, base_delay: std::time::Duration) -> Self {
+        self.retry_policy = Some(retry::RetryPolicy { max_attempts, base_delay });
+        self
+    }
+
+    /// Caps retries across the whole client at `ratio` retries per request,
+    /// see [`RetryBudget`]. Without a budget retries are not limited globally.
+    pub fn with_retry_budget(mut self, ratio: f64) -> Self {
+        self.retry_budget = Some(Arc::new(RetryBudget::new(ratio)));
+        self
+    }
+
+    /// The client-wide retry budget, if one was configured.
+    pub fn retry_budget(&self) -> Option<&RetryBudget> {
+        self.retry_budget.as_deref()
+    }
+
+    /// Stops making requests for `cooldown` after `failure_threshold`
+    /// consecutive failures, see [`CircuitBreaker`]. Clones of the client
+    /// share the breaker.
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: std::time::Duration) -> Self {
+        self.circuit_breaker = Some(Arc::new(CircuitBreaker::new(failure_threshold, cooldown)));
+        self
+    }
+
+    /// Spaces out requests to at most `requests_per_second`, across all
+    /// clones of the client. Calls over the limit wait for their turn rather
+    /// than failing. A rate that isn't positive and finite disables the limit,
+    /// and one under a request a day is raised to that.
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limiter = (requests_per_second.is_finite() && requests_per_second > 0.0)
+            .then(|| Arc::new(rate_limit::RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    /// The circuit breaker, if one was configured.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_deref()
+    }
+
+    /// Caps the size of response bodies. Larger responses are abandoned while
+    /// streaming, before being fully buffered, with
+    /// [`GrowattError::ResponseTooLarge`]. Defaults to [`DEFAULT_MAX_RESPONSE_SIZE`].
+    pub fn with_max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = bytes;
+        self
+    }
+
+    /// Caps how many pages a method that walks all pages of a list may fetch.
+    /// Past the cap it fails with [`GrowattError::PageLimitExceeded`] instead
+    /// of trusting the server's page count. Defaults to [`DEFAULT_MAX_PAGES`].
+    pub fn with_max_pages(mut self, pages: usize) -> Self {
+        self.max_pages = pages;
+        self
+    }
+
+    /// Sets how many plants [`Growatt::get_all_plant_data`] and
+    /// [`Growatt::get_portfolio_summary`] fetch at once, and how many devices
+    /// [`Growatt::set_param_bulk`] writes to at once. Defaults to
+    /// [`DEFAULT_PLANT_CONCURRENCY`]; zero is treated as one.
+    pub fn with_plant_concurrency(mut self, concurrency: usize) -> Self {
+        self.plant_concurrency = concurrency.max(1);
+        self
+    }
+
+    // Called before fetching another page, with the number already fetched
+    pub(crate) fn check_page_limit(&self, fetched: usize) -> Result<()> {
+        if fetched >= self.max_pages {
+            return Err(GrowattError::PageLimitExceeded { limit: self.max_pages });
+        }
+        Ok(())
+    }
+
+    /// Caches [`Growatt::get_plant`] results per plant.
+    ///
+    /// Within `ttl` of a fetch the cached data is returned directly. Within a
+    /// further `stale_grace` the stale data is still returned immediately while
+    /// a background task fetches a fresh copy for later calls. Older entries
+    /// are fetched again before returning.
+    #[cfg(not(feature = "minimal"))]
+    pub fn with_plant_data_cache(mut self, ttl: std::time::Duration, stale_grace: std::time::Duration) -> Self {
+        self.plant_cache = Some(Arc::new(cache::PlantDataCache::new(ttl, stale_grace)));
+        self
+    }
+
+    /// Converts [`PlantData`] energies to Wh, and [`Weather`] temperatures to
+    /// °C, whatever units the account is configured to display (see
+    /// [`Growatt::account_units`]). Plant powers are always returned in W.
+    ///
+    /// Only these two are converted. Other typed results, e.g.
+    /// [`MixTotalData`] or [`EnergyChart`], keep the units the portal sends
+    /// them in, and without this plant data and weather are returned in the
+    /// account's units too, e.g. energy in kWh by default.
+    pub fn with_si_plant_units(mut self) -> Self {
+        self.si_units = true;
+        self
+    }
+
+    /// The display units configured for the account, detected at login.
+    pub fn account_units(&self) -> Option<AccountUnits> {
+        match self.recovered_session() {
+            Some(recovered) => recovered.account_units,
+            None => self.account_units,
+        }
+    }
+
+    // The units to convert from, when conversion to SI units is enabled
+    fn si_conversion(&self) -> Option<AccountUnits> {
+        self.si_units.then(|| self.account_units().unwrap_or_default())
+    }
+
+    /// Registers a hook called after every HTTP request the client makes, with
+    /// the endpoint, duration and outcome, e.g. to feed a metrics pipeline.
+    pub fn on_request_complete(mut self, hook: impl Fn(RequestMetrics) + Send + Sync + 'static) -> Self {
+        self.on_request_complete = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback that sees every response body, with the request
+    /// path, before it is parsed, e.g. to log the actual payload when a typed
+    /// parse fails. Off by default.
+    ///
+    /// Bodies can hold account details and session tokens; take care where
+    /// they end up.
+    #[cfg(not(feature = "minimal"))]
+    pub fn with_response_inspector(mut self, inspector: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        self.response_inspector = Some(Arc::new(inspector));
+        self
+    }
+
+    /// Keeps the headers of the latest response, readable with
+    /// [`Growatt::last_response_headers`]. Useful to see which backend node
+    /// served a request or whether a proxy cached it.
+    #[cfg(not(feature = "minimal"))]
+    pub fn with_header_capture(mut self) -> Self {
+        self.last_headers = Some(Arc::default());
+        self
+    }
+
+    /// Headers of the latest response, if [`Growatt::with_header_capture`] is
+    /// enabled and a response has arrived. Clones of the client share them.
+    #[cfg(not(feature = "minimal"))]
+    pub fn last_response_headers(&self) -> Option<reqwest::header::HeaderMap> {
+        let last_headers = self.last_headers.as_ref()?;
+        last_headers.lock().unwrap().clone()
+    }
+
+    /// Sets the `isReadPact` value sent with the login form, which tells the
+    /// server whether the user has accepted the terms of use. Defaults to `true`.
+    pub fn with_read_pact(mut self, accepted: bool) -> Self {
+        self.read_pact = accepted;
+        self
+    }
+
     fn hash_password(&self, password: &str) -> String {
         let mut hasher = Md5::new();
         hasher.update(password.as_bytes());
         hex::encode(hasher.finalize())
     }
 
-    pub fn get_token(&self) -> Option<String> {
-        self.token.clone()
+    pub fn get_token(&self) -> Option<String> {
+        match self.recovered_session() {
+            Some(recovered) => recovered.token,
+            None => self.token.clone(),
+        }
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<bool> {
+        // If already logged in with a valid session, return early
+        if self.is_logged_in && self.is_session_valid() {
+            return Ok(true);
+        }
+
+        self.login_detailed(username, password).await.map(|_| true)
+    }
+
+    /// Logs in like [`Growatt::login`] but returns the account details, such as
+    /// its [`Role`]. Always performs a fresh login, even if a session is active.
+    pub async fn login_detailed(&mut self, username: &str, password: &str) -> Result<LoginResult> {
+        self.login_inner(username, password, "").await
+    }
+
+    /// Logs in with the answer to a captcha, after a login failed with
+    /// [`GrowattError::CaptchaRequired`]. Always performs a fresh login.
+    pub async fn login_with_captcha(&mut self, username: &str, password: &str, validate_code: &str) -> Result<LoginResult> {
+        self.login_inner(username, password, validate_code).await
+    }
+
+    async fn login_inner(&mut self, username: &str, password: &str, validate_code: &str) -> Result<LoginResult> {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+
+        let password_hash = self.hash_password(password);
+
+        let mut json_response = self.send_login_form(username, &password_hash, validate_code).await?;
+
+        // Accounts that never accepted the terms through the app are turned away
+        // until the acceptance handshake has been done, after which a retry works
+        if json_response.get("result").and_then(|v| v.as_i64()) == Some(LOGIN_RESULT_TERMS_REQUIRED) {
+            tracing::info!("Terms of use not accepted yet, accepting and retrying login");
+            if self.accept_terms().await? {
+                json_response = self.send_login_form(username, &password_hash, validate_code).await?;
+            }
+        }
+
+        if let Some(result) = json_response.get("result").and_then(|v| v.as_i64()) {
+            if result == 1 {
+                self.is_logged_in = true;
+                // Set session expiry time
+                self.session_expiry = Some(Utc::now() + self.session_duration);
+                
+                *self.session_gate.write().await = false;
+                self.login_generation = self.session_generation.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                let login_result = LoginResult::from_value(&json_response);
+                self.account_units = Some(AccountUnits::from_value(&json_response));
+
+                // Store token if available in the response
+                if let Some(token) = &login_result.token {
+                    self.token = Some(token.clone());
+                }
+
+                tracing::info!("Logged in as {}", username);
+                Ok(login_result)
+            } else {
+                let error = auth::login_failure(&json_response);
+
+                tracing::warn!("Login failed with error: {}", error);
+                self.is_logged_in = false;
+                self.session_expiry = None;
+                Err(error)
+            }
+        } else {
+            self.is_logged_in = false;
+            self.session_expiry = None;
+            Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            ))
+        }
+    }
+
+    // Reads a response body, giving up as soon as it exceeds the size cap
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<(Option<String>, String)> {
+        let limit = self.max_response_size;
+        let too_large = GrowattError::ResponseTooLarge { limit };
+
+        if response.content_length().is_some_and(|length| length > limit as u64) {
+            return Err(too_large);
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        #[cfg(not(feature = "minimal"))]
+        let path = response.url().path().to_string();
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(too_large);
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let body = String::from_utf8_lossy(&body).into_owned();
+        #[cfg(not(feature = "minimal"))]
+        if let Some(inspector) = &self.response_inspector {
+            inspector(&path, &body);
+        }
+        Ok((content_type, body))
+    }
+
+    // Joins an endpoint path onto the base URL, keeping any path prefix the
+    // base URL carries, with or without a trailing slash
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+    }
+
+    // Sends a request and reads its response with `read`, then reports the
+    // outcome of both to the request hook: a login page or an unparseable body
+    // is a failure even though the HTTP exchange itself succeeded.
+    //
+    // Clones of the client share a session, so a logout on one of them waits
+    // for requests in flight on the others, and later requests fail with
+    // NotLoggedIn instead of reaching the server with a dead cookie.
+    async fn execute<T, F, Fut>(&self, request: reqwest::RequestBuilder, read: F) -> Result<T>
+    where
+        F: FnOnce(reqwest::Response) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        self.exchange(request, true, read).await
+    }
+
+    // Like `execute`, without waiting on the session gate, for the login and
+    // logout calls that change the session themselves
+    async fn send_request<T, F, Fut>(&self, request: reqwest::RequestBuilder, read: F) -> Result<T>
+    where
+        F: FnOnce(reqwest::Response) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        self.exchange(request, false, read).await
+    }
+
+    async fn exchange<T, F, Fut>(&self, request: reqwest::RequestBuilder, gated: bool, read: F) -> Result<T>
+    where
+        F: FnOnce(reqwest::Response) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut request = request.build()?;
+        request.timeout_mut().get_or_insert(self.timeout);
+        let endpoint = request.url().path().to_string();
+        let started = std::time::Instant::now();
+
+        let (result, retries) = match self.send(request, gated).await {
+            (Ok(response), retries) => (read(response).await, retries),
+            (Err(e), retries) => (Err(e), retries),
+        };
+
+        if let Some(hook) = &self.on_request_complete {
+            hook(RequestMetrics {
+                endpoint,
+                duration: started.elapsed(),
+                outcome: result.as_ref().map(|_| ()).map_err(GrowattError::kind),
+                retries,
+            });
+        }
+
+        result
+    }
+
+    // Sends a request through the retry policy and circuit breaker, returning
+    // the response whatever its status and how many retries were made
+    async fn send(&self, request: reqwest::Request, gated: bool) -> (Result<reqwest::Response>, u32) {
+        // Held until the response arrives, so a logout waits for the request
+        let _logged_out = if gated {
+            let logged_out = self.session_gate.read().await;
+            if *logged_out {
+                return (Err(GrowattError::NotLoggedIn), 0);
+            }
+            Some(logged_out)
+        } else {
+            None
+        };
+
+        if self.circuit_breaker.as_ref().is_some_and(|breaker| !breaker.allow()) {
+            return (Err(GrowattError::CircuitOpen), 0);
+        }
+
+        let (result, retries) = retry::send_with_retry(
+            &self.client,
+            request,
+            self.retry_policy.as_ref(),
+            self.retry_budget.as_deref(),
+            self.rate_limiter.as_deref(),
+        )
+        .await;
+
+        if let Some(breaker) = &self.circuit_breaker {
+            match &result {
+                Ok(response) if !response.status().is_server_error() => breaker.record_success(),
+                _ => breaker.record_failure(),
+            }
+        }
+
+        #[cfg(not(feature = "minimal"))]
+        if let (Some(last_headers), Ok(response)) = (&self.last_headers, &result) {
+            *last_headers.lock().unwrap() = Some(response.headers().clone());
+        }
+
+        (result.map_err(GrowattError::from), retries)
+    }
+
+    // Shared handler for data calls: fails on an error status, then reads the
+    // body and parses it as JSON
+    async fn read_json(&self, response: reqwest::Response) -> Result<serde_json::Value> {
+        response.error_for_status_ref()?;
+
+        let (content_type, body) = self.read_body(response).await?;
+        parse_json_body(content_type.as_deref(), &body)
+    }
+
+    // Sends a request and parses its JSON body, whatever its content.
+    //
+    // A session the server dropped early gets the login page, so the request
+    // is sent once more after logging in again, see `recover_expired_session`.
+    async fn request_json(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let generation = self.session_generation.load(std::sync::atomic::Ordering::SeqCst);
+        let retry = request.try_clone();
+
+        match self.request_json_once(request).await {
+            Err(GrowattError::SessionExpired) => {
+                let Some(retry) = retry else {
+                    return Err(GrowattError::SessionExpired);
+                };
+                if !self.recover_expired_session(generation).await? {
+                    return Err(GrowattError::SessionExpired);
+                }
+                self.request_json_once(retry).await
+            }
+            result => result,
+        }
+    }
+
+    async fn request_json_once(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        self.execute(request, |response| self.read_json(response)).await
+    }
+
+    // Logs in again after the server served its login page to a session it
+    // dropped early, returning whether the call can be retried. Without
+    // stored credentials the expiry is left to the caller.
+    //
+    // The login runs on a copy of the client, which shares the cookie jar, so
+    // concurrent fetches over `&self` recover too. Calls that hit the same
+    // expiry wait for one login instead of each starting their own.
+    async fn recover_expired_session(&self, generation: u64) -> Result<bool> {
+        let _relogin = self.relogin_lock.lock().await;
+        if self.session_generation.load(std::sync::atomic::Ordering::SeqCst) != generation {
+            return Ok(true);
+        }
+
+        let has_credentials = !self.credentials.is_empty() || (self.username.is_some() && self.password.is_some());
+        if !has_credentials {
+            return Ok(false);
+        }
+        tracing::debug!("Session expired early, logging in again");
+        let mut client = self.clone();
+        let recovered = client.reconnect().await?;
+        if recovered {
+            *self.recovered_session.lock().unwrap() = Some(RecoveredSession {
+                generation: client.login_generation,
+                expiry: client.session_expiry,
+                token: client.token,
+                account_units: client.account_units,
+            });
+        }
+        Ok(recovered)
+    }
+
+    // The session a copy of this client recovered, if newer than its own login
+    fn recovered_session(&self) -> Option<RecoveredSession> {
+        self.recovered_session
+            .lock()
+            .unwrap()
+            .clone()
+            .filter(|recovered| recovered.generation > self.login_generation)
+    }
+
+    // Takes over the session a copy of this client recovered, if any
+    fn adopt_recovered_session(&mut self) {
+        if let Some(recovered) = self.recovered_session() {
+            self.is_logged_in = true;
+            self.login_generation = recovered.generation;
+            self.session_expiry = recovered.expiry;
+            self.token = recovered.token;
+            self.account_units = recovered.account_units;
+        }
+    }
+
+    // Sends a request and returns its JSON body, failing on an error status or
+    // an empty body
+    async fn send_json(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let json_response = self.request_json(request).await?;
+        if is_empty_value(&json_response) {
+            return Err(empty_response());
+        }
+        Ok(json_response)
+    }
+
+    // Posts a form over an already-checked session, so it can run concurrently
+    pub(crate) async fn fetch_form(&self, path: &str, form: &[(&str, &str)]) -> Result<serde_json::Value> {
+        let request = self.client
+            .post(self.endpoint(path))
+            .form(form);
+
+        self.send_json(request).await
+    }
+
+    // Logs in if needed, posts a form and parses the response
+    pub(crate) async fn post_form<T: serde::de::DeserializeOwned>(&mut self, path: &str, form: &[(&str, &str)]) -> Result<T> {
+        self.check_login().await?;
+        let json_response = self.fetch_form(path, form).await.inspect_err(|e| {
+            if matches!(e, GrowattError::SessionExpired) {
+                self.is_logged_in = false;
+            }
+        })?;
+        Ok(serde_json::from_value(json_response)?)
+    }
+
+    // Posts a form to a per-plant endpoint and returns the response's obj
+    pub(crate) async fn post_plant_form(&mut self, path: &str, plant_id: &str, form: &[(&str, &str)]) -> Result<serde_json::Value> {
+        let json_response: serde_json::Value = self
+            .post_form(&format!("{}?plantId={}", path, plant_id), form)
+            .await?;
+        check_plant_access(&json_response, plant_id)?;
+        response_obj(json_response)
     }
 
-    pub async fn login(&mut self, username: &str, password: &str) -> Result<bool> {
-        // If already logged in with a valid session, return early
-        if self.is_logged_in && self.is_session_valid() {
-            return Ok(true);
-        }
-
-        self.username = Some(username.to_string());
-        self.password = Some(password.to_string());
-
-        let password_hash = self.hash_password(password);
+    /// Posts a form to a portal endpoint this crate doesn't wrap, e.g.
+    /// `call_raw("/panel/getDevicesByPlant?plantId=123", &[])`, and returns the
+    /// JSON response. Logs in first if needed, like the other methods.
+    pub async fn call_raw(&mut self, path: &str, form: &[(&str, &str)]) -> Result<serde_json::Value> {
+        self.post_form(path, form).await
+    }
 
+    async fn send_login_form(&self, username: &str, password_hash: &str, validate_code: &str) -> Result<serde_json::Value> {
         let form = [
             ("account", username),
             ("password", ""),
-            ("validateCode", ""),
-            ("isReadPact", "1"),
-            ("passwordCrc", &password_hash),
+            ("validateCode", validate_code),
+            ("isReadPact", if self.read_pact { "1" } else { "0" }),
+            ("passwordCrc", password_hash),
         ];
 
-        let response = self.client
-            .post(format!("{}/login", self.base_url))
+        let request = self.client
+            .post(self.endpoint("/login"))
             .header("Content-Type", "application/x-www-form-urlencoded; charset=UTF-8")
-            .form(&form)
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
+            .form(&form);
+
+        let json_response = self
+            .send_request(request, |response| async move {
+                response.error_for_status_ref()?;
+
+                let (_, body) = self.read_body(response).await?;
+                Ok(serde_json::from_str::<serde_json::Value>(&body)?)
+            })
             .await?;
 
-        response.error_for_status_ref()?;
+        // The raw response carries the session token, so it stays off by default
+        tracing::trace!("Login response: {}", json_response);
 
-        let json_response: serde_json::Value = response.json().await?;
+        Ok(json_response)
+    }
 
-        println!("Login response: {}", json_response);
+    /// Accepts the portal's terms of use for the stored account.
+    ///
+    /// `login` calls this automatically when the server refuses a login because
+    /// the terms have not been accepted yet.
+    pub async fn accept_terms(&mut self) -> Result<bool> {
+        let username = self.username.clone().ok_or(GrowattError::NotLoggedIn)?;
 
-        if let Some(result) = json_response.get("result").and_then(|v| v.as_i64()) {
-            if result == 1 {
-                self.is_logged_in = true;
-                // Set session expiry time
-                self.session_expiry = Some(Utc::now() + self.session_duration);
-                
-                // Extract and store token if available in the response
-                if let Some(token) = json_response.get("token").and_then(|v| v.as_str()) {
-                    self.token = Some(token.to_string());
-                }
-                
-                Ok(true)
-            } else {
-                let error_msg = json_response
-                    .get("msg")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string();
-                
-                println!("Login failed with error: {}", error_msg);
-                self.is_logged_in = false;
-                self.session_expiry = None;
-                Err(GrowattError::AuthError(error_msg))
-            }
-        } else {
-            self.is_logged_in = false;
-            self.session_expiry = None;
-            Err(GrowattError::InvalidResponse(
-                "Invalid response structure".to_string(),
-            ))
+        let form = [("account", username.as_str()), ("isReadPact", "1")];
+
+        let request = self.client
+            .post(self.endpoint("/login/acceptPact"))
+            .form(&form);
+
+        let json_response = self.send_request(request, |response| self.read_json(response)).await?;
+
+        let accepted = json_response.get("result").and_then(|v| v.as_i64()) == Some(1)
+            || json_response.get("success").and_then(value_as_bool) == Some(true);
+        if accepted {
+            self.read_pact = true;
         }
+
+        Ok(accepted)
     }
 
-    // Check if the current session is valid
-    fn is_session_valid(&self) -> bool {
-        if let Some(expiry) = self.session_expiry {
+    /// Whether the current session is still within its expiry. The server
+    /// may still end it earlier.
+    pub fn is_session_valid(&self) -> bool {
+        if let Some(expiry) = self.session_expiry() {
             Utc::now() < expiry
         } else {
             false
         }
     }
 
+    /// When the current session is due to expire, or `None` without a session.
+    pub fn session_expiry(&self) -> Option<DateTime<Utc>> {
+        match self.recovered_session() {
+            Some(recovered) => recovered.expiry,
+            None => self.session_expiry,
+        }
+    }
+
+    /// Time left until the session expires, e.g. to log in again ahead of
+    /// time. Zero once it has expired, `None` without a session.
+    pub fn time_until_expiry(&self) -> Option<chrono::Duration> {
+        self.session_expiry()
+            .map(|expiry| (expiry - Utc::now()).max(chrono::Duration::zero()))
+    }
+
+    /// Forces a fresh session: clears the cookies and session state, then logs
+    /// in again with the stored credentials.
+    ///
+    /// Unlike the automatic relogin on expiry this always re-authenticates, which
+    /// helps recover a session the server invalidated early. The client's
+    /// configuration, such as its proxy and timeouts, is kept. Returns
+    /// [`GrowattError::NotLoggedIn`] if no credentials are stored.
+    pub async fn reconnect(&mut self) -> Result<bool> {
+        if !self.credentials.is_empty() {
+            self.reset_session();
+            return self.login_with_failover().await.map(|_| true);
+        }
+
+        let (Some(username), Some(password)) = (self.username.clone(), self.password.clone()) else {
+            return Err(GrowattError::NotLoggedIn);
+        };
+
+        self.reset_session();
+        self.login(&username, &password).await
+    }
+
+    /// Same as [`Growatt::reconnect`].
+    pub async fn relogin(&mut self) -> Result<bool> {
+        self.reconnect().await
+    }
+
+    // Drops all session state, including cookies, without contacting the server
+    fn reset_session(&mut self) {
+        self.cookies.clear();
+        *self.recovered_session.lock().unwrap() = None;
+        self.is_logged_in = false;
+        self.session_expiry = None;
+        self.token = None;
+    }
+
     // Ensure a valid session exists, auto-login if needed
     async fn ensure_session(&mut self) -> Result<()> {
+        self.adopt_recovered_session();
         if !self.is_logged_in || !self.is_session_valid() {
-            if let (Some(username), Some(password)) = (self.username.clone(), self.password.clone()) {
+            if !self.credentials.is_empty() {
+                self.login_with_failover().await?;
+            } else if let (Some(username), Some(password)) = (self.username.clone(), self.password.clone()) {
                 self.login(&username, &password).await?;
             } else {
                 return Err(GrowattError::NotLoggedIn);
@@ -247,36 +1480,41 @@ impl Growatt {
 
     pub async fn logout(&mut self) -> Result<bool> {
         if !self.is_logged_in {
-            println!("No active session to log out from.");
+            tracing::debug!("No active session to log out from");
             return Ok(false);
         }
 
         // Create request with all headers in a more concise way
-        let response = self.client
-            .get(format!("{}/logout", self.base_url))
+        let request = self.client
+            .get(self.endpoint("/logout"))
             .header("Accept-Language", "en-US,en;q=0.9")
             .header("Upgrade-Insecure-Requests", "1")
-            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36")
             .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7")
             .header("Sec-Fetch-Site", "same-origin")
             .header("Sec-Fetch-Mode", "navigate")
             .header("Sec-Fetch-User", "?1")
             .header("Sec-Fetch-Dest", "document")
-            .header("Referer", format!("{}/index", self.base_url))
-            .send()
-            .await?;
+            .header("Referer", self.endpoint("/index"));
+
+        // Waits for requests in flight on clones of this client, and holds
+        // back new ones until the logout is done
+        let mut logged_out = self.session_gate.write().await;
 
         // Growatt returns 302 redirect on successful logout
-        let status = response.status().as_u16();
+        let status = self
+            .send_request(request, |response| async move { Ok(response.status().as_u16()) })
+            .await?;
         let success = status == 302;
         
         // Update the session state based on the result
         if success {
+            *logged_out = true;
+            *self.recovered_session.lock().unwrap() = None;
             self.is_logged_in = false;
             self.session_expiry = None;
-            println!("Successfully logged out.");
+            tracing::info!("Successfully logged out");
         } else {
-            println!("Logout returned unexpected status code: {}", status);
+            tracing::warn!("Logout returned unexpected status code: {}", status);
         }
         
         Ok(success)
@@ -287,49 +1525,23 @@ impl Growatt {
         self.ensure_session().await
     }
 
+    /// Lists the account's plants. An account without plants gets an empty
+    /// list; a response that isn't a plant list at all, such as the login
+    /// page served to an expired session, is an error.
     pub async fn get_plants(&mut self) -> Result<PlantList> {
-        self.check_login().await?;
-
-        let response = self.client
-            .post(format!("{}/index/getPlantListTitle", self.base_url))
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.as_array().map_or(true, |arr| arr.is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            let plants: Vec<Plant> = serde_json::from_value(json_response)?;
-            Ok(PlantList(plants))
+        if self.uses_openapi() {
+            return self.openapi_get_plants().await;
         }
-    }
 
-    pub async fn get_plant(&mut self, plant_id: &str) -> Result<PlantData> {
         self.check_login().await?;
 
-        let response = self.client
-            .post(format!("{}/panel/getPlantData?plantId={}", self.base_url, plant_id))
-            .send()
-            .await?;
+        let json_response = self.fetch_plant_list().await?;
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if let Some(obj) = json_response.get("obj") {
-            if obj.is_null() || (obj.is_object() && obj.as_object().unwrap().is_empty()) {
-                Err(GrowattError::InvalidResponse(
-                    "Empty response. Please ensure you are logged in.".to_string(),
-                ))
-            } else {
-                let plant_data: PlantData = serde_json::from_value(obj.clone())?;
-                Ok(plant_data)
-            }
+        if json_response.is_array() {
+            let plants: Vec<Plant> = serde_json::from_value(json_response)?;
+            Ok(PlantList(plants))
+        } else if is_empty_value(&json_response) {
+            Err(empty_response())
         } else {
             Err(GrowattError::InvalidResponse(
                 "Invalid response structure".to_string(),
@@ -337,235 +1549,219 @@ impl Growatt {
         }
     }
 
-    pub async fn get_mix_ids(&mut self, plant_id: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
-
-        let response = self.client
-            .post(format!("{}/panel/getDevicesByPlant?plantId={}", self.base_url, plant_id))
-            .send()
-            .await?;
+    async fn fetch_plant_list(&self) -> Result<serde_json::Value> {
+        let request = self.client
+            .post(self.endpoint("/index/getPlantListTitle"));
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if let Some(obj) = json_response.get("obj").and_then(|o| o.get("mix")) {
-            if obj.is_null() || (obj.is_array() && obj.as_array().unwrap().is_empty()) {
-                Err(GrowattError::InvalidResponse(
-                    "Empty response. Please ensure you are logged in.".to_string(),
-                ))
-            } else {
-                Ok(obj.clone())
-            }
-        } else {
-            Err(GrowattError::InvalidResponse(
-                "Invalid response structure".to_string(),
-            ))
-        }
+        self.request_json(request).await
     }
 
-    pub async fn get_mix_total(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
-
-        let form = [("mixSn", mix_sn)];
+    /// Logs in and lists the account's plants in one call. A rejected login
+    /// fails with the same errors as [`Growatt::login`].
+    pub async fn login_and_list(&mut self, username: &str, password: &str) -> Result<PlantList> {
+        self.login(username, password).await?;
+        self.get_plants().await
+    }
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXTotalData?plantId={}", self.base_url, plant_id))
-            .form(&form)
-            .send()
-            .await?;
+    /// Lists the account's plants and fetches each one's data concurrently,
+    /// up to [`Growatt::with_plant_concurrency`] at a time.
+    ///
+    /// Only a failure to list the plants fails the call; each plant's fetch
+    /// gets its own result, in no particular order.
+    pub async fn get_all_plant_data(&mut self) -> Result<Vec<(String, Result<PlantData>)>> {
+        let plants = self.get_plants().await?;
+
+        let this = &*self;
+        let results = futures::stream::iter(plants.0)
+            .map(|plant| async move {
+                let result = if this.uses_openapi() {
+                    this.openapi_get_plant(&plant.plant_id).await
+                } else {
+                    this.fetch_plant(&plant.plant_id).await
+                };
+                (plant.plant_id, result)
+            })
+            .buffer_unordered(self.plant_concurrency)
+            .collect()
+            .await;
+
+        Ok(results)
+    }
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if let Some(obj) = json_response.get("obj") {
-            if obj.is_null() || (obj.is_object() && obj.as_object().unwrap().is_empty()) {
-                Err(GrowattError::InvalidResponse(
-                    "Empty response. Please ensure you are logged in.".to_string(),
-                ))
-            } else {
-                Ok(obj.clone())
+    pub async fn get_plant(&mut self, plant_id: &str) -> Result<PlantData> {
+        #[cfg(not(feature = "minimal"))]
+        if let Some(cache) = self.plant_cache.clone() {
+            match cache.lookup(plant_id) {
+                cache::Lookup::Fresh(data) => return Ok(data),
+                cache::Lookup::Stale { data, refresh } => {
+                    if refresh {
+                        self.spawn_plant_refresh(cache, plant_id);
+                    }
+                    return Ok(data);
+                }
+                cache::Lookup::Miss => {}
             }
-        } else {
-            Err(GrowattError::InvalidResponse(
-                "Invalid response structure".to_string(),
-            ))
+
+            let data = self.get_plant_uncached(plant_id).await?;
+            cache.store(plant_id, data.clone());
+            return Ok(data);
         }
+
+        self.get_plant_uncached(plant_id).await
     }
 
-    pub async fn get_mix_status(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
+    /// Like [`Growatt::get_plant`], but always fetches from the server, e.g.
+    /// for a user-initiated refresh. The result replaces any cached copy.
+    pub async fn get_plant_fresh(&mut self, plant_id: &str) -> Result<PlantData> {
+        let data = self.get_plant_uncached(plant_id).await?;
 
-        let form = [("mixSn", mix_sn)];
+        #[cfg(not(feature = "minimal"))]
+        if let Some(cache) = &self.plant_cache {
+            cache.store(plant_id, data.clone());
+        }
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXStatusData?plantId={}", self.base_url, plant_id))
-            .form(&form)
-            .send()
-            .await?;
+        Ok(data)
+    }
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if let Some(obj) = json_response.get("obj") {
-            if obj.is_null() || (obj.is_object() && obj.as_object().unwrap().is_empty()) {
-                Err(GrowattError::InvalidResponse(
-                    "Empty response. Please ensure you are logged in.".to_string(),
-                ))
-            } else {
-                Ok(obj.clone())
-            }
-        } else {
-            Err(GrowattError::InvalidResponse(
-                "Invalid response structure".to_string(),
-            ))
+    async fn get_plant_uncached(&mut self, plant_id: &str) -> Result<PlantData> {
+        if self.uses_openapi() {
+            return self.openapi_get_plant(plant_id).await;
         }
-    }
 
-    pub async fn get_energy_stats_daily(&mut self, date: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
         self.check_login().await?;
+        self.fetch_plant(plant_id).await
+    }
 
-        let form = [
-            ("date", date),
-            ("plantId", plant_id),
-            ("mixSn", mix_sn),
-        ];
+    // Refreshes a stale cache entry on a copy of the client, sharing its session
+    #[cfg(not(feature = "minimal"))]
+    fn spawn_plant_refresh(&self, cache: Arc<cache::PlantDataCache>, plant_id: &str) {
+        let mut client = self.clone();
+        let plant_id = plant_id.to_string();
+        tokio::spawn(async move {
+            match client.get_plant_uncached(&plant_id).await {
+                Ok(data) => cache.store(&plant_id, data),
+                Err(_) => cache.refresh_failed(&plant_id),
+            }
+        });
+    }
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXEnergyDayChart", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
+    // Fetches plant data over an already-checked session, so it can run concurrently
+    async fn fetch_plant(&self, plant_id: &str) -> Result<PlantData> {
+        let obj = self.fetch_plant_obj(plant_id).await?;
+        self.plant_data_from_obj(obj)
+    }
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
+    // Reads a getPlantData object into PlantData, in the configured units
+    fn plant_data_from_obj(&self, obj: serde_json::Value) -> Result<PlantData> {
+        let mut plant_data: PlantData = serde_json::from_value(obj)?;
+        plant_data.normalize_units(&self.account_units().unwrap_or_default());
+        if let Some(units) = self.si_conversion() {
+            plant_data.to_si(&units);
         }
+        Ok(plant_data)
     }
 
-    pub async fn get_energy_stats_monthly(&mut self, date: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
+    // Fetches the raw getPlantData object, which carries more than PlantData models
+    async fn fetch_plant_obj(&self, plant_id: &str) -> Result<serde_json::Value> {
+        let json_response = self
+            .fetch_form(&format!("/panel/getPlantData?plantId={}", plant_id), &[])
+            .await?;
+        check_plant_access(&json_response, plant_id)?;
+        response_obj(json_response)
+    }
 
-        let form = [
-            ("date", date),
-            ("plantId", plant_id),
-            ("mixSn", mix_sn),
-        ];
+    /// Fetches the plant data and reports whether the plant is currently clipping.
+    ///
+    /// Returns `None` when the plant payload lacks a rated AC power.
+    pub async fn get_clipping_status(&mut self, plant_id: &str) -> Result<Option<bool>> {
+        let plant_data = self.get_plant(plant_id).await?;
+        Ok(plant_data.is_clipping())
+    }
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXEnergyMonthChart", self.base_url))
-            .form(&form)
-            .send()
+    pub async fn get_mix_ids(&mut self, plant_id: &str) -> Result<serde_json::Value> {
+        let json_response: serde_json::Value = self
+            .post_form(&format!("/panel/getDevicesByPlant?plantId={}", plant_id), &[])
             .await?;
+        check_plant_access(&json_response, plant_id)?;
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
+        match json_response.get("obj").and_then(|obj| obj.get("mix")) {
+            Some(mix) if is_empty_value(mix) => Err(empty_response()),
+            Some(mix) => Ok(mix.clone()),
+            None => Err(GrowattError::InvalidResponse(
+                "Invalid response structure".to_string(),
+            )),
         }
     }
 
-    pub async fn get_energy_stats_yearly(&mut self, year: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
+    /// Fetches a MIX inverter's energy totals. See [`Growatt::get_mix_total_raw`]
+    /// for the untyped payload.
+    pub async fn get_mix_total(&mut self, plant_id: &str, mix_sn: &str) -> Result<MixTotalData> {
+        let obj = self.get_mix_total_raw(plant_id, mix_sn).await?;
+        MixTotalData::try_from(&obj)
+    }
 
-        let form = [
-            ("year", year),
-            ("plantId", plant_id),
-            ("mixSn", mix_sn),
-        ];
+    pub async fn get_mix_total_raw(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        self.post_plant_form("/panel/mix/getMIXTotalData", plant_id, &[("mixSn", mix_sn)]).await
+    }
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXEnergyYearChart", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
+    pub async fn get_mix_status(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        self.post_plant_form("/panel/mix/getMIXStatusData", plant_id, &[("mixSn", mix_sn)]).await
+    }
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+    #[deprecated(note = "use get_energy_stats_daily_typed")]
+    pub async fn get_energy_stats_daily(&mut self, date: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        self.post_chart_form("/panel/mix/getMIXEnergyDayChart", ("date", date), plant_id, mix_sn).await
+    }
+
+    #[deprecated(note = "use get_energy_stats_monthly_typed")]
+    pub async fn get_energy_stats_monthly(&mut self, date: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        self.post_chart_form("/panel/mix/getMIXEnergyMonthChart", ("date", date), plant_id, mix_sn).await
+    }
+
+    #[deprecated(note = "use get_energy_stats_yearly_typed")]
+    pub async fn get_energy_stats_yearly(&mut self, year: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
+        self.post_chart_form("/panel/mix/getMIXEnergyYearChart", ("year", year), plant_id, mix_sn).await
     }
 
+    #[deprecated(note = "use get_energy_stats_total_typed")]
     pub async fn get_energy_stats_total(&mut self, year: &str, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
+        self.post_chart_form("/panel/mix/getMIXEnergyTotalChart", ("year", year), plant_id, mix_sn).await
+    }
 
+    // Posts a MIX chart request, `anchor` being the endpoint's date field
+    pub(crate) async fn post_chart_form(
+        &mut self,
+        path: &str,
+        anchor: (&str, &str),
+        plant_id: &str,
+        mix_sn: &str,
+    ) -> Result<serde_json::Value> {
         let form = [
-            ("year", year),
+            anchor,
             ("plantId", plant_id),
             ("mixSn", mix_sn),
         ];
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXEnergyTotalChart", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.post_form(path, &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
     pub async fn get_weekly_battery_stats(&mut self, plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
-
         let form = [
             ("plantId", plant_id),
             ("mixSn", mix_sn),
         ];
 
-        let response = self.client
-            .post(format!("{}/panel/mix/getMIXBatChart", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.post_form("/panel/mix/getMIXBatChart", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
+    /// Sets the MIX inverter's clock to this machine's local time, despite the
+    /// name, and returns the raw response. See [`Growatt::sync_mix_time_now`]
+    /// for whether the server accepted it.
     pub async fn post_mix_ac_discharge_time_period_now(&mut self, _plant_id: &str, mix_sn: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
-
         let now = Local::now();
         let param1 = now.format("%Y-%m-%d %H:%M:%S").to_string();
 
@@ -576,82 +1772,54 @@ impl Growatt {
             ("param1", &param1),
         ];
 
-        let response = self.client
-            .post(format!("{}/tcpSet.do", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
+        self.post_form("/tcpSet.do", &form).await
+    }
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+    /// Sets the MIX inverter's clock to this machine's local time and returns
+    /// whether the server accepted it. See [`Growatt::set_device_time`] to send
+    /// another time zone's time.
+    pub async fn sync_mix_time_now(&mut self, mix_sn: &str) -> Result<bool> {
+        let param1 = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.set_inverter_param(mix_sn, "pf_sys_year", &[("param1", &param1)]).await
     }
 
     pub async fn get_device_list(&mut self, plant_id: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
+        if self.uses_openapi() {
+            return self.openapi_get_device_list(plant_id).await;
+        }
 
         let form = [
             ("plantId", plant_id),
             ("currPage", "1"),
         ];
 
-        let response = self.client
-            .post(format!("{}/device/getMAXList", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.post_form("/device/getMAXList", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
+    /// Lists the plant's environmental monitoring sensors (`getEnvList`).
+    ///
+    /// Despite the name this is on-site sensor hardware, not a weather service;
+    /// use [`Growatt::get_plant_weather`] for the weather at the plant's location.
     pub async fn get_weather(&mut self, plant_id: &str) -> Result<serde_json::Value> {
-        self.check_login().await?;
-
         let form = [
             ("plantId", plant_id),
             ("currPage", "1"),
         ];
 
-        let response = self.client
-            .post(format!("{}/device/getEnvList", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.post_form("/device/getEnvList", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
     pub async fn get_devices_by_plant_list(&mut self, plant_id: &str, curr_page: Option<i32>) -> Result<serde_json::Value> {
         self.check_login().await?;
+        self.fetch_devices_by_plant_list(plant_id, curr_page).await
+    }
 
+    // Fetches a device page over an already-checked session, so it can run concurrently
+    async fn fetch_devices_by_plant_list(&self, plant_id: &str, curr_page: Option<i32>) -> Result<serde_json::Value> {
         let curr_page = curr_page.unwrap_or(1).to_string();
 
         let form = [
@@ -659,23 +1827,9 @@ impl Growatt {
             ("currPage", &curr_page),
         ];
 
-        let response = self.client
-            .post(format!("{}/panel/getDevicesByPlantList", self.base_url))
-            .form(&form)
-            .send()
-            .await?;
-
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse(
-                "Empty response. Please ensure you are logged in.".to_string(),
-            ))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.fetch_form("/panel/getDevicesByPlantList", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
     pub async fn get_fault_logs(
@@ -709,24 +1863,16 @@ impl Growatt {
             ("deviceFlag", &device_flag.to_string()),
         ];
 
-        let response = self.client
-            .post(format!("{}/log/getNewPlantFaultLog", self.base_url))
+        let request = self.client
+            .post(self.endpoint("/log/getNewPlantFaultLog"))
             .header("Content-Type", "application/x-www-form-urlencoded; charset=UTF-8")
             .header("X-Requested-With", "XMLHttpRequest")
             .header("Accept", "application/json, text/javascript, */*; q=0.01")
-            .form(&form)
-            .send()
-            .await?;
+            .form(&form);
 
-        response.error_for_status_ref()?;
-        
-        let json_response: serde_json::Value = response.json().await?;
-        
-        if json_response.is_null() || (json_response.is_object() && json_response.as_object().unwrap().is_empty()) {
-            Err(GrowattError::InvalidResponse("Empty response received from server".to_string()))
-        } else {
-            Ok(json_response)
-        }
+        let json_response = self.send_json(request).await?;
+        check_plant_access(&json_response, plant_id)?;
+        Ok(json_response)
     }
 
     // Alias for backward compatibility
@@ -744,7 +1890,7 @@ impl Growatt {
 
     // Add a public method to check login status
     pub fn is_logged_in(&self) -> bool {
-        self.is_logged_in
+        self.is_logged_in || self.recovered_session().is_some()
     }
 }
 
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{value_as_f64, Growatt, Result};
+
+/// Readings of one phase of a smart meter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhaseReading {
+    /// Active power (W); positive when importing from the grid.
+    pub active_power: Option<f64>,
+    /// Voltage (V).
+    pub voltage: Option<f64>,
+    /// Current (A).
+    pub current: Option<f64>,
+}
+
+impl PhaseReading {
+    // Phase suffixes are A/B/C on newer meters and R/S/T on older ones
+    fn from_value(obj: &serde_json::Value, phase: char, legacy_phase: char) -> Self {
+        let number = |key: &str, legacy_key: &str| {
+            obj.get(format!("{}{}", key, phase))
+                .or_else(|| obj.get(format!("{}{}", legacy_key, legacy_phase)))
+                .and_then(value_as_f64)
+        };
+
+        Self {
+            active_power: number("activePower", "pac"),
+            voltage: number("voltage", "vac"),
+            current: number("current", "iac"),
+        }
+    }
+
+    // Single-phase meters report the missing phases as absent or all zero
+    fn is_populated(&self) -> bool {
+        [self.active_power, self.voltage, self.current]
+            .iter()
+            .any(|value| value.is_some_and(|value| value != 0.0))
+    }
+}
+
+/// Per-phase readings of a smart meter. Phases B and C are `None` on
+/// single-phase meters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeterPhases {
+    pub a: PhaseReading,
+    pub b: Option<PhaseReading>,
+    pub c: Option<PhaseReading>,
+}
+
+impl MeterPhases {
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Self {
+        let populated = |phase: PhaseReading| phase.is_populated().then_some(phase);
+
+        Self {
+            a: PhaseReading::from_value(obj, 'A', 'R'),
+            b: populated(PhaseReading::from_value(obj, 'B', 'S')),
+            c: populated(PhaseReading::from_value(obj, 'C', 'T')),
+        }
+    }
+
+    pub fn is_three_phase(&self) -> bool {
+        self.b.is_some() || self.c.is_some()
+    }
+}
+
+impl Growatt {
+    /// Fetches the per-phase power, voltage and current of a smart meter.
+    pub async fn get_meter_phases(&mut self, plant_id: &str, meter_sn: &str) -> Result<MeterPhases> {
+        let form = [("meterSn", meter_sn)];
+        let obj = self.post_plant_form("/panel/meter/getMeterStatusData", plant_id, &form).await?;
+        Ok(MeterPhases::from_value(&obj))
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use crate::GrowattError;
+
+/// The kind of a [`GrowattError`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Request,
+    Json,
+    Auth,
+    InvalidResponse,
+    NotLoggedIn,
+    SessionExpired,
+    ResponseTooLarge,
+    NoData,
+    Forbidden,
+    PageLimitExceeded,
+    CircuitOpen,
+    NotApplied,
+    PartiallyApplied,
+    InvalidConfig,
+    InvalidInput,
+    Io,
+    CaptchaRequired,
+    InvalidCredentials,
+    AccountLocked,
+    AccountFrozen,
+    AccountNotFound,
+}
+
+impl GrowattError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            GrowattError::RequestError(_) => ErrorKind::Request,
+            GrowattError::JsonError(_) => ErrorKind::Json,
+            GrowattError::AuthError(_) => ErrorKind::Auth,
+            GrowattError::InvalidResponse(_) => ErrorKind::InvalidResponse,
+            GrowattError::NotLoggedIn => ErrorKind::NotLoggedIn,
+            GrowattError::SessionExpired => ErrorKind::SessionExpired,
+            GrowattError::ResponseTooLarge { .. } => ErrorKind::ResponseTooLarge,
+            GrowattError::NoData => ErrorKind::NoData,
+            GrowattError::Forbidden { .. } => ErrorKind::Forbidden,
+            GrowattError::PageLimitExceeded { .. } => ErrorKind::PageLimitExceeded,
+            GrowattError::CircuitOpen => ErrorKind::CircuitOpen,
+            GrowattError::NotApplied { .. } => ErrorKind::NotApplied,
+            GrowattError::PartiallyApplied { .. } => ErrorKind::PartiallyApplied,
+            GrowattError::InvalidConfig(_) => ErrorKind::InvalidConfig,
+            GrowattError::InvalidInput(_) => ErrorKind::InvalidInput,
+            GrowattError::Io(_) => ErrorKind::Io,
+            GrowattError::CaptchaRequired { .. } => ErrorKind::CaptchaRequired,
+            GrowattError::InvalidCredentials => ErrorKind::InvalidCredentials,
+            GrowattError::AccountLocked => ErrorKind::AccountLocked,
+            GrowattError::AccountFrozen => ErrorKind::AccountFrozen,
+            GrowattError::AccountNotFound => ErrorKind::AccountNotFound,
+        }
+    }
+}
+
+/// Reported to the [`Growatt::on_request_complete`](crate::Growatt::on_request_complete)
+/// hook after every HTTP request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestMetrics {
+    /// Path of the request, without the host or query, e.g. `/panel/getPlantData`.
+    pub endpoint: String,
+    /// Time until the response was read and parsed, or the request failed.
+    pub duration: Duration,
+    /// `Err` with the kind of the error when the request failed, the server
+    /// answered with an error status, or the response couldn't be parsed, e.g.
+    /// [`ErrorKind::SessionExpired`] for a login page. Requests the client
+    /// refused to send, such as with the circuit breaker open, are reported too.
+    pub outcome: std::result::Result<(), ErrorKind>,
+    /// How many times the request was retried before this outcome.
+    pub retries: u32,
+}
+
+pub(crate) type RequestHook = dyn Fn(RequestMetrics) + Send + Sync;
+
+// Sees each response body with its path, before it is parsed
+#[cfg(not(feature = "minimal"))]
+pub(crate) type ResponseInspector = dyn Fn(&str, &str) + Send + Sync;
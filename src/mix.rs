@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+use crate::devices::{device_state, DeviceState};
+use crate::serde_helpers::{deserialize_lenient_bool, deserialize_lenient_f64, deserialize_lenient_i64, from_result_value};
+use crate::{Growatt, GrowattError, Result};
+
+/// A MIX inverter entry from `getDevicesByPlant`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MixDevice {
+    #[serde(rename = "deviceSn")]
+    pub device_sn: String,
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Whether the datalogger has lost its connection to the server.
+    #[serde(default, deserialize_with = "deserialize_lenient_bool")]
+    pub lost: Option<bool>,
+    #[serde(default, alias = "deviceStatus", deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+    #[serde(rename = "datalogSn", default)]
+    pub datalog_sn: Option<String>,
+}
+
+impl MixDevice {
+    /// Whether the device's datalogger is connected. Devices that don't report
+    /// `lost` are assumed to be connected.
+    pub fn is_online(&self) -> bool {
+        !self.lost.unwrap_or(false)
+    }
+
+    /// The device's state from its `lost` flag and `status` code, see
+    /// [`resolve_device_state`](crate::resolve_device_state).
+    pub fn state(&self) -> Option<DeviceState> {
+        device_state(self.lost, self.status)
+    }
+}
+
+/// Totals reported by `getMIXTotalData` for a MIX inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MixTotalData {
+    /// PV energy generated today (kWh).
+    #[serde(rename = "epvToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_today: Option<f64>,
+    /// PV energy generated over the inverter's lifetime (kWh).
+    #[serde(rename = "epvTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_total: Option<f64>,
+    /// PV energy generated today by the first string (kWh).
+    #[serde(rename = "epv1Today", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv1_today: Option<f64>,
+    /// PV energy generated today by the second string (kWh).
+    #[serde(rename = "epv2Today", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv2_today: Option<f64>,
+    /// Energy charged into the battery today (kWh).
+    #[serde(rename = "eChargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_charge_today: Option<f64>,
+    /// Energy discharged from the battery today (kWh).
+    #[serde(rename = "eDischargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_discharge_today: Option<f64>,
+    /// Energy consumed by the local load today (kWh).
+    #[serde(rename = "elocalLoadToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub elocal_load_today: Option<f64>,
+    /// CO2 emissions avoided (kg).
+    #[serde(rename = "co2Reduction", default, deserialize_with = "deserialize_lenient_f64")]
+    pub co2_reduction: Option<f64>,
+    /// Standard coal saved (kg).
+    #[serde(rename = "coalReduction", default, deserialize_with = "deserialize_lenient_f64")]
+    pub coal_reduction: Option<f64>,
+    /// Equivalent number of trees planted.
+    #[serde(rename = "treeNumber", default, deserialize_with = "deserialize_lenient_f64")]
+    pub tree_number: Option<f64>,
+    /// Energy exported to the grid over the inverter's lifetime (kWh).
+    #[serde(rename = "eToGridTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_to_grid_total: Option<f64>,
+    /// Energy imported from the grid over the inverter's lifetime (kWh).
+    #[serde(rename = "eToUserTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_to_user_total: Option<f64>,
+    /// Energy consumed by the local load over the inverter's lifetime (kWh).
+    #[serde(rename = "elocalLoadTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub elocal_load_total: Option<f64>,
+}
+
+/// Converts a raw [`Growatt::get_mix_total_raw`] result, or a full response with
+/// the totals under `obj`.
+impl TryFrom<&serde_json::Value> for MixTotalData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+/// Real-time status reported by `getMIXStatusData` for a MIX inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MixStatusData {
+    /// Battery discharge power (kW).
+    #[serde(rename = "pdisCharge1", default, deserialize_with = "deserialize_lenient_f64")]
+    pub pdis_charge1: Option<f64>,
+    /// Battery charge power (kW).
+    #[serde(rename = "chargePower", default, deserialize_with = "deserialize_lenient_f64")]
+    pub charge_power: Option<f64>,
+    /// Work mode priority: 0 load first, 1 battery first, 2 grid first.
+    #[serde(rename = "priorityChoose", default, deserialize_with = "deserialize_lenient_i64")]
+    pub priority_choose: Option<i64>,
+    /// Power imported from the grid (kW).
+    #[serde(rename = "pactouser", default, deserialize_with = "deserialize_lenient_f64")]
+    pub pactouser: Option<f64>,
+    /// Power exported to the grid (kW).
+    #[serde(rename = "pactogrid", default, deserialize_with = "deserialize_lenient_f64")]
+    pub pactogrid: Option<f64>,
+    /// Power used by the local load (kW).
+    #[serde(rename = "pLocalLoad", default, deserialize_with = "deserialize_lenient_f64")]
+    pub p_local_load: Option<f64>,
+    /// Battery state of charge (%).
+    #[serde(default, deserialize_with = "deserialize_lenient_f64")]
+    pub soc: Option<f64>,
+    /// Battery voltage (V).
+    #[serde(rename = "vBat", default, deserialize_with = "deserialize_lenient_f64")]
+    pub v_bat: Option<f64>,
+    /// Grid voltage (V).
+    #[serde(rename = "vAc1", default, deserialize_with = "deserialize_lenient_f64")]
+    pub v_ac1: Option<f64>,
+    /// Grid frequency (Hz).
+    #[serde(rename = "fAc", default, deserialize_with = "deserialize_lenient_f64")]
+    pub f_ac: Option<f64>,
+    /// Raw run state code, see [`MixStatusData::run_state`].
+    #[serde(default, deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+}
+
+impl MixStatusData {
+    pub fn run_state(&self) -> Option<MixRunState> {
+        self.status.map(MixRunState::from)
+    }
+}
+
+/// Converts a raw [`Growatt::get_mix_status`] result, or a full response with
+/// the status under `obj`.
+impl TryFrom<&serde_json::Value> for MixStatusData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+/// The run state of a MIX inverter, from its status `status` code.
+///
+/// | Code | State      |
+/// |------|------------|
+/// | 0    | `Waiting`  |
+/// | 1    | `Normal`   |
+/// | 2    | `Checking` |
+/// | 3    | `Fault`    |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MixRunState {
+    Waiting,
+    Normal,
+    Checking,
+    Fault,
+    /// A code this crate doesn't know.
+    Unknown(i64),
+}
+
+impl From<i64> for MixRunState {
+    fn from(code: i64) -> Self {
+        match code {
+            0 => MixRunState::Waiting,
+            1 => MixRunState::Normal,
+            2 => MixRunState::Checking,
+            3 => MixRunState::Fault,
+            other => MixRunState::Unknown(other),
+        }
+    }
+}
+
+/// SPH inverters report their totals with the same fields as MIX inverters.
+pub type SphTotalData = MixTotalData;
+
+/// The grid exchange counters of a [`MixTotalData`]. They only ever grow, so
+/// the difference between two reads is the energy exchanged in between.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GridExchange {
+    pub imported_kwh: Option<f64>,
+    pub exported_kwh: Option<f64>,
+}
+
+/// The environmental-impact figures of a [`MixTotalData`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentalImpact {
+    /// CO2 emissions avoided (kg).
+    pub co2_reduction: Option<f64>,
+    /// Standard coal saved (kg).
+    pub coal_reduction: Option<f64>,
+    /// Equivalent number of trees planted.
+    pub trees_planted: Option<f64>,
+}
+
+impl MixTotalData {
+    pub fn environmental_impact(&self) -> EnvironmentalImpact {
+        EnvironmentalImpact {
+            co2_reduction: self.co2_reduction,
+            coal_reduction: self.coal_reduction,
+            trees_planted: self.tree_number,
+        }
+    }
+
+    /// Share of the generated PV energy used on site rather than exported,
+    /// from 0 to 1, over the inverter's lifetime.
+    ///
+    /// Computed from the grid export counter, so PV energy stored in the
+    /// battery and used later counts once, as self-consumed, rather than being
+    /// counted again when the battery discharges. Returns `None` when a counter
+    /// is missing or nothing was generated.
+    pub fn self_consumption_ratio(&self) -> Option<f64> {
+        let generated = self.epv_total.filter(|pv| *pv > 0.0)?;
+        let exported = self.e_to_grid_total?;
+        Some(((generated - exported) / generated).clamp(0.0, 1.0))
+    }
+
+    /// Share of the consumed energy supplied by PV and battery rather than
+    /// imported from the grid, from 0 to 1, over the inverter's lifetime.
+    ///
+    /// Computed from the grid import counter, so battery energy counts as
+    /// independent only when it didn't come from the grid. Returns `None` when
+    /// a counter is missing or nothing was consumed.
+    pub fn grid_independence_ratio(&self) -> Option<f64> {
+        let consumed = self.elocal_load_total.filter(|load| *load > 0.0)?;
+        let imported = self.e_to_user_total?;
+        Some(((consumed - imported) / consumed).clamp(0.0, 1.0))
+    }
+
+    pub fn grid_exchange(&self) -> GridExchange {
+        GridExchange {
+            imported_kwh: self.e_to_user_total,
+            exported_kwh: self.e_to_grid_total,
+        }
+    }
+}
+
+impl Growatt {
+    /// Typed variant of [`Growatt::get_mix_status`].
+    pub async fn get_mix_status_typed(&mut self, plant_id: &str, mix_sn: &str) -> Result<MixStatusData> {
+        let obj = self.get_mix_status(plant_id, mix_sn).await?;
+        MixStatusData::try_from(&obj)
+    }
+
+    /// Lists the plant's MIX inverters as typed entries, see [`Growatt::get_mix_ids`]
+    /// for the raw array.
+    pub async fn get_mix_devices(&mut self, plant_id: &str) -> Result<Vec<MixDevice>> {
+        let mix = self.get_mix_ids(plant_id).await?;
+        Ok(serde_json::from_value(mix)?)
+    }
+}
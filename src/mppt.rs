@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{response_obj, value_as_f64, Growatt, Result};
+
+/// Highest tracker number probed when the device's tracker count is unknown.
+const MAX_MPPT_FIELDS: usize = 16;
+
+/// Nameplate details of an inverter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub device_sn: String,
+    pub model: Option<String>,
+    pub firmware_version: Option<String>,
+    /// DC nameplate power of the panels on this inverter (W), as with
+    /// [`PlantData::nominal_power`](crate::PlantData::nominal_power).
+    pub nominal_power: Option<f64>,
+    /// Number of MPPT trackers (PV inputs) the inverter has.
+    pub mppt_count: Option<usize>,
+}
+
+impl DeviceInfo {
+    pub(crate) fn from_value(device_sn: &str, obj: &serde_json::Value) -> Self {
+        let text = |key: &str| {
+            obj.get(key)
+                .and_then(|value| value.as_str())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        Self {
+            device_sn: device_sn.to_string(),
+            model: text("modelText").or_else(|| text("model")),
+            firmware_version: text("fwVersion"),
+            nominal_power: obj.get("nominalPower").and_then(value_as_f64),
+            mppt_count: obj
+                .get("mpptNum")
+                .or_else(|| obj.get("trackerNum"))
+                .and_then(value_as_f64)
+                .filter(|count| *count > 0.0)
+                .map(|count| count as usize),
+        }
+    }
+}
+
+/// Live readings of one MPPT tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mppt {
+    /// Tracker number, starting at 1.
+    pub index: usize,
+    /// Input voltage (V).
+    pub voltage: Option<f64>,
+    /// Input current (A).
+    pub current: Option<f64>,
+    /// Input power (W).
+    pub power: Option<f64>,
+}
+
+impl Mppt {
+    fn is_zero(&self) -> bool {
+        [self.voltage, self.current, self.power]
+            .iter()
+            .all(|value| value.is_none_or(|value| value == 0.0))
+    }
+}
+
+// Responses pad vpvN/ipvN/ppvN out to a fixed number of fields whatever the
+// hardware has. With a known tracker count exactly that many are returned,
+// up to the field limit; otherwise trailing all-zero trackers are dropped as
+// padding.
+pub(crate) fn parse_mppts(obj: &serde_json::Value, mppt_count: Option<usize>) -> Vec<Mppt> {
+    let mut mppts: Vec<Mppt> = (1..=mppt_count.map_or(MAX_MPPT_FIELDS, |count| count.min(MAX_MPPT_FIELDS)))
+        .map(|index| {
+            let number = |key: &str| obj.get(format!("{}{}", key, index)).and_then(value_as_f64);
+            Mppt {
+                index,
+                voltage: number("vpv"),
+                current: number("ipv"),
+                power: number("ppv"),
+            }
+        })
+        .collect();
+
+    if mppt_count.is_none() {
+        while mppts.last().is_some_and(Mppt::is_zero) {
+            mppts.pop();
+        }
+    }
+
+    mppts
+}
+
+impl Growatt {
+    /// Fetches an inverter's nameplate details, including its tracker count.
+    pub async fn get_device_info(&mut self, device_sn: &str) -> Result<DeviceInfo> {
+        self.check_login().await?;
+
+        let obj = self.fetch_device_obj("/panel/getDeviceInfo", device_sn).await?;
+        let mut info = DeviceInfo::from_value(device_sn, &obj);
+
+        // Reported in the account's power unit, like the plant's powers
+        let units = self.account_units().unwrap_or_default();
+        info.nominal_power = info.nominal_power.map(|power| units.to_watts(power));
+        Ok(info)
+    }
+
+    /// Fetches the live per-tracker (string) readings of an inverter.
+    ///
+    /// Uses the tracker count from [`Growatt::get_device_info`] so that the
+    /// zero fields the server pads its response with aren't reported as dead
+    /// strings.
+    pub async fn get_string_data(&mut self, device_sn: &str) -> Result<Vec<Mppt>> {
+        self.check_login().await?;
+
+        let info = self.fetch_device_obj("/panel/getDeviceInfo", device_sn).await?;
+        let mppt_count = DeviceInfo::from_value(device_sn, &info).mppt_count;
+
+        let obj = self.fetch_device_obj("/panel/getDeviceRealtimeData", device_sn).await?;
+        Ok(parse_mppts(&obj, mppt_count))
+    }
+
+    // Posts a device serial and returns the response's non-empty obj
+    async fn fetch_device_obj(&self, path: &str, device_sn: &str) -> Result<serde_json::Value> {
+        let form = [("deviceSn", device_sn)];
+        response_obj(self.fetch_form(path, &form).await?)
+    }
+}
@@ -0,0 +1,141 @@
+// The openapi.growatt.com v1 REST API. That host doesn't serve the web portal
+// endpoints used elsewhere: it authenticates each request with an API token in
+// a `token` header instead of a login session, and wraps responses in an
+// {"error_code", "error_msg", "data"} envelope.
+
+use crate::{value_as_f64, AccountUnits, Growatt, GrowattError, Plant, PlantData, PlantList, Result};
+
+/// Host of the v1 REST API.
+pub(crate) const OPENAPI_BASE_URL: &str = "https://openapi.growatt.com";
+
+/// Error codes the v1 API returns for a missing or invalid token.
+const TOKEN_ERROR_CODES: [i64; 2] = [10011, 10012];
+
+/// How a client authenticates and which endpoints it calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiMode {
+    /// The web portal endpoints, behind a username/password login.
+    WebPortal,
+    /// The v1 REST API, with an API token in each request. Selected by
+    /// [`Growatt::with_api_token`] or [`Growatt::with_alternate_url`].
+    OpenApiV1,
+}
+
+impl Growatt {
+    /// Which API the client calls. Methods the v1 API has no equivalent for
+    /// use the portal endpoints in either mode.
+    pub fn api_mode(&self) -> ApiMode {
+        if self.openapi {
+            ApiMode::OpenApiV1
+        } else {
+            ApiMode::WebPortal
+        }
+    }
+
+    /// Whether requests go to the v1 REST API, see [`Growatt::api_mode`].
+    pub fn uses_openapi(&self) -> bool {
+        self.api_mode() == ApiMode::OpenApiV1
+    }
+
+    // Builds an authenticated GET, failing if no token was set
+    pub(crate) fn openapi_request(&self, path: &str, query: &[(&str, &str)]) -> Result<reqwest::RequestBuilder> {
+        let token = self.api_token.as_deref().ok_or_else(|| {
+            GrowattError::InvalidConfig("the v1 API needs a token, see with_api_token".to_string())
+        })?;
+
+        Ok(self.client
+            .get(self.endpoint(path))
+            .header("token", token)
+            .query(query))
+    }
+
+    // Sends an authenticated GET and unwraps the response envelope
+    pub(crate) async fn openapi_get(&self, path: &str, query: &[(&str, &str)]) -> Result<serde_json::Value> {
+        let request = self.openapi_request(path, query)?;
+
+        self.execute(request, |response| async move { unwrap_envelope(self.read_json(response).await?) })
+            .await
+    }
+
+    // GET /v1/plant/list
+    pub(crate) async fn openapi_get_plants(&self) -> Result<PlantList> {
+        let data = self.openapi_get("/v1/plant/list", &[]).await?;
+
+        let plants = data
+            .get("plants")
+            .and_then(|plants| plants.as_array())
+            .map(|plants| plants.iter().filter_map(plant_from_v1).collect())
+            .unwrap_or_default();
+
+        Ok(PlantList(plants))
+    }
+
+    // GET /v1/plant/data
+    pub(crate) async fn openapi_get_plant(&self, plant_id: &str) -> Result<PlantData> {
+        let data = self.openapi_get("/v1/plant/data", &[("plant_id", plant_id)]).await?;
+        let number = |key: &str| data.get(key).and_then(value_as_f64);
+
+        let mut plant_data = PlantData {
+            plant_name: None,
+            plant_id: Some(plant_id.to_string()),
+            capacity: number("peak_power_actual"),
+            today_energy: number("today_energy"),
+            total_energy: number("total_energy"),
+            current_power: number("current_power"),
+            current_power_str: None,
+            rated_power: None,
+            nominal_power: None,
+        };
+
+        // The v1 API always reports W and kWh, whatever the account displays
+        if self.si_units {
+            plant_data.to_si(&AccountUnits::default());
+        }
+        Ok(plant_data)
+    }
+
+    // GET /v1/device/inverter/list
+    pub(crate) async fn openapi_get_device_list(&self, plant_id: &str) -> Result<serde_json::Value> {
+        self.openapi_get("/v1/device/inverter/list", &[("plant_id", plant_id)]).await
+    }
+}
+
+// Takes the data out of a v1 response, or turns its error code into an error
+fn unwrap_envelope(mut json_response: serde_json::Value) -> Result<serde_json::Value> {
+    let error_code = json_response.get("error_code").and_then(value_as_f64).map(|code| code as i64);
+    match error_code {
+        Some(0) => Ok(json_response.get_mut("data").map(serde_json::Value::take).unwrap_or_default()),
+        Some(code) => {
+            let message = json_response
+                .get("error_msg")
+                .and_then(|msg| msg.as_str())
+                .unwrap_or("Unknown error")
+                .to_string();
+            if TOKEN_ERROR_CODES.contains(&code) {
+                Err(GrowattError::AuthError(message))
+            } else {
+                Err(GrowattError::InvalidResponse(format!("Error {}: {}", code, message)))
+            }
+        }
+        None => Err(GrowattError::InvalidResponse(
+            "Invalid response structure".to_string(),
+        )),
+    }
+}
+
+// v1 plant IDs are numbers where the portal uses strings
+fn plant_from_v1(plant: &serde_json::Value) -> Option<Plant> {
+    let plant_id = match plant.get("plant_id")? {
+        serde_json::Value::String(id) => id.clone(),
+        id => id.as_i64()?.to_string(),
+    };
+
+    Some(Plant {
+        plant_id,
+        plant_name: plant.get("name").and_then(|name| name.as_str()).unwrap_or_default().to_string(),
+        plant_address: plant.get("city").and_then(|city| city.as_str()).map(str::to_string),
+        plant_watts: plant.get("current_power").and_then(value_as_f64),
+        is_share: None,
+        shared_by: None,
+    })
+}
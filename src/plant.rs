@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::{deserialize_lenient_f64, from_result_value};
+use crate::{value_as_f64, EnvironmentalImpact, Growatt, GrowattError, Result};
+
+/// The electricity tariff configured for a plant in the portal, used there to
+/// compute savings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tariff {
+    /// Price per kWh of consumed energy (`formulaMoney`).
+    pub price_per_kwh: Option<f64>,
+    /// Currency of the prices, e.g. `"euro"` or `"THB"`.
+    pub currency: Option<String>,
+    /// Price per kWh paid for exported energy, when a separate one is configured.
+    pub feed_in_rate: Option<f64>,
+}
+
+impl Tariff {
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Self {
+        let text = |key: &str| {
+            obj.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+        };
+
+        // plantMoneyText looks like "0.2 (euro/kWh)"; fall back to its unit
+        // when the unit id field is missing
+        let currency = text("formulaMoneyUnitId").or_else(|| {
+            let money_text = text("plantMoneyText")?;
+            let unit = money_text.split_once('(')?.1.split(['/', ')']).next()?.trim();
+            (!unit.is_empty()).then(|| unit.to_string())
+        });
+
+        Self {
+            price_per_kwh: obj.get("formulaMoney").and_then(value_as_f64),
+            currency,
+            feed_in_rate: obj.get("feedInPrice").and_then(value_as_f64),
+        }
+    }
+}
+
+/// A plant's location, as configured in the portal.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Address {
+    pub country: Option<String>,
+    pub city: Option<String>,
+    /// The first line of the free-text address, up to the first comma.
+    pub street: Option<String>,
+    /// The free-text address as entered (`plantAddress`).
+    pub raw: Option<String>,
+}
+
+impl Address {
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Self {
+        let text = |key: &str| {
+            obj.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+        };
+
+        let raw = text("plantAddress");
+        let street = raw
+            .as_deref()
+            .and_then(|raw| raw.split(',').next())
+            .map(str::trim)
+            .filter(|street| !street.is_empty())
+            .map(str::to_owned);
+
+        Self {
+            country: text("country"),
+            city: text("city"),
+            street,
+            raw,
+        }
+    }
+}
+
+/// The PV modules a plant was set up with. Fields are `None` when the plant
+/// wasn't configured with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PanelConfig {
+    /// Number of modules (`panelNum`).
+    pub panel_count: Option<u32>,
+    /// Rated power of one module (W, `panelPower`).
+    pub panel_watts: Option<u32>,
+}
+
+impl PanelConfig {
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Self {
+        // Unconfigured plants report 0 rather than leaving the fields out
+        let positive = |key: &str| {
+            obj.get(key)
+                .and_then(value_as_f64)
+                .filter(|n| *n > 0.0)
+                .map(|n| n as u32)
+        };
+
+        Self {
+            panel_count: positive("panelNum"),
+            panel_watts: positive("panelPower"),
+        }
+    }
+
+    /// Installed DC capacity (W): module count times module power.
+    pub fn total_dc_capacity(&self) -> Option<u32> {
+        self.panel_count?.checked_mul(self.panel_watts?)
+    }
+}
+
+/// A plant's aggregate figures reported by `getPlantTotalData`, as shown on
+/// the portal's summary tile.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlantTotal {
+    /// Energy generated today (kWh).
+    #[serde(rename = "eToday", alias = "todayEnergy", default, deserialize_with = "deserialize_lenient_f64")]
+    pub energy_today: Option<f64>,
+    /// Energy generated this month (kWh).
+    #[serde(rename = "eMonth", alias = "monthEnergy", default, deserialize_with = "deserialize_lenient_f64")]
+    pub energy_month: Option<f64>,
+    /// Energy generated over the plant's lifetime (kWh).
+    #[serde(rename = "eTotal", alias = "totalEnergy", default, deserialize_with = "deserialize_lenient_f64")]
+    pub energy_total: Option<f64>,
+    /// Revenue today, in [`PlantTotal::currency`].
+    #[serde(rename = "mToday", alias = "todayMoney", default, deserialize_with = "deserialize_lenient_f64")]
+    pub revenue_today: Option<f64>,
+    /// Revenue over the plant's lifetime, in [`PlantTotal::currency`].
+    #[serde(rename = "mTotal", alias = "totalMoney", default, deserialize_with = "deserialize_lenient_f64")]
+    pub revenue_total: Option<f64>,
+    /// Currency of the revenue figures, e.g. `"euro"`.
+    #[serde(rename = "moneyUnit", alias = "mUnitText", default)]
+    pub currency: Option<String>,
+    /// CO2 emissions avoided (kg).
+    #[serde(rename = "co2", alias = "co2Reduction", default, deserialize_with = "deserialize_lenient_f64")]
+    pub co2_reduction: Option<f64>,
+    /// Standard coal saved (kg).
+    #[serde(rename = "coal", alias = "coalReduction", default, deserialize_with = "deserialize_lenient_f64")]
+    pub coal_reduction: Option<f64>,
+    /// Equivalent number of trees planted.
+    #[serde(rename = "tree", alias = "treeNumber", default, deserialize_with = "deserialize_lenient_f64")]
+    pub trees_planted: Option<f64>,
+}
+
+impl PlantTotal {
+    pub fn environmental_impact(&self) -> EnvironmentalImpact {
+        EnvironmentalImpact {
+            co2_reduction: self.co2_reduction,
+            coal_reduction: self.coal_reduction,
+            trees_planted: self.trees_planted,
+        }
+    }
+}
+
+/// Converts a raw plant total result, or a full response with the totals
+/// under `obj`.
+impl TryFrom<&serde_json::Value> for PlantTotal {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+impl Growatt {
+    /// Fetches the plant's lifetime and daily totals: energy, revenue and
+    /// environmental impact.
+    pub async fn get_plant_total(&mut self, plant_id: &str) -> Result<PlantTotal> {
+        let form = [("plantId", plant_id)];
+        let obj = self.post_plant_form("/panel/getPlantTotalData", plant_id, &form).await?;
+        PlantTotal::try_from(&obj)
+    }
+
+    /// Reads the electricity tariff configured for the plant.
+    pub async fn get_plant_tariff(&mut self, plant_id: &str) -> Result<Tariff> {
+        self.check_login().await?;
+        let obj = self.fetch_plant_obj(plant_id).await?;
+        Ok(Tariff::from_value(&obj))
+    }
+
+    /// Reads the plant's address, split into country, city and street.
+    pub async fn get_plant_address(&mut self, plant_id: &str) -> Result<Address> {
+        self.check_login().await?;
+        let obj = self.fetch_plant_obj(plant_id).await?;
+        Ok(Address::from_value(&obj))
+    }
+
+    /// Reads the plant's module count and module power, as entered at setup.
+    pub async fn get_plant_panel_config(&mut self, plant_id: &str) -> Result<PanelConfig> {
+        self.check_login().await?;
+        let obj = self.fetch_plant_obj(plant_id).await?;
+        Ok(PanelConfig::from_value(&obj))
+    }
+}
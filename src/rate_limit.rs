@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+// Spaces requests evenly at a fixed rate, shared by every clone of a client.
+// Callers reserve the next free slot and wait for it outside the lock, so
+// concurrent calls queue up in order without holding each other up.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+// The slowest rate accepted, one request a day. Much slower rates have an
+// interval too long for `Duration` and `Instant` to hold.
+pub(crate) const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 86_400.0;
+
+impl RateLimiter {
+    // Rates under the minimum are raised to it
+    pub(crate) fn new(requests_per_second: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / requests_per_second.max(MIN_REQUESTS_PER_SECOND)),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Waits until a request may be sent
+    pub(crate) async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
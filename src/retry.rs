@@ -0,0 +1,205 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::time::Instant;
+
+use crate::rate_limit::RateLimiter;
+
+/// Most retries the budget can save up, which bounds the burst of retries
+/// allowed when failures start.
+const RETRY_BUDGET_CAP: f64 = 10.0;
+
+/// A client-wide allowance of retries, shared by every call.
+///
+/// Each request deposits `ratio` tokens and each retry withdraws one, so over
+/// time at most `ratio` retries are made per request (plus a small reserve
+/// for bursts). During a broad outage the budget runs dry and failed calls
+/// stop being retried, instead of every call multiplying the load on an
+/// already struggling server. This is the same scheme as gRPC's retry
+/// throttling.
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    balance: Mutex<f64>,
+}
+
+impl RetryBudget {
+    /// Creates a budget allowing `ratio` retries per request, e.g. `0.1` for
+    /// one retry per ten requests. Negative or non-finite ratios allow none.
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio: if ratio.is_finite() { ratio.max(0.0) } else { 0.0 },
+            balance: Mutex::new(RETRY_BUDGET_CAP),
+        }
+    }
+
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Retries currently available.
+    pub fn available(&self) -> f64 {
+        *self.balance.lock().unwrap()
+    }
+
+    /// Records a request, earning `ratio` retry tokens.
+    pub fn deposit(&self) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance = (*balance + self.ratio).min(RETRY_BUDGET_CAP);
+    }
+
+    /// Takes a token for a retry; returns `false` if the budget is exhausted
+    /// and the retry should not be made.
+    pub fn try_withdraw(&self) -> bool {
+        let mut balance = self.balance.lock().unwrap();
+        if *balance >= 1.0 {
+            *balance -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Stops calling the server after repeated failures.
+///
+/// After `failure_threshold` consecutive failed requests the breaker opens and
+/// requests fail straight away with
+/// [`GrowattError::CircuitOpen`](crate::GrowattError::CircuitOpen). Once
+/// `cooldown` has passed a single trial request is let through: success closes
+/// the breaker, failure keeps it open for another cooldown. Transport errors
+/// and 5xx responses count as failures.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    // When the breaker last opened, or last let a trial request through
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Whether requests are currently being refused.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.opened_at.is_some_and(|opened_at| opened_at.elapsed() < self.cooldown)
+    }
+
+    // Decides whether a request may be sent. After the cooldown one trial
+    // request is allowed, and the cooldown restarts so the next one waits for
+    // its outcome (or another cooldown, if it never reports back).
+    pub(crate) fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.opened_at {
+            None => true,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => false,
+            Some(_) => {
+                state.opened_at = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState::default();
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.opened_at.is_some() || state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Longest a request is retried for, counted from its first attempt, so a
+/// caller's own timeout isn't outlasted by backoff.
+const MAX_RETRY_ELAPSED: Duration = Duration::from_secs(60);
+
+// Retry settings from `with_retry`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) base_delay: Duration,
+}
+
+impl RetryPolicy {
+    // Exponential backoff from the base delay, plus up to 50% random jitter so
+    // clients that failed together don't retry in lockstep
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(1 << retry.min(16));
+        let jitter = rand::thread_rng().gen_range(Duration::ZERO..=backoff / 2);
+        backoff + jitter
+    }
+}
+
+// Timeouts and 5xx responses are worth another try; anything else, including
+// 4xx responses, would fail the same way again
+fn is_transient(result: &reqwest::Result<reqwest::Response>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error(),
+        Err(e) => e.is_timeout(),
+    }
+}
+
+// Sends a request, retrying transient failures per the policy and within the
+// budget. Returns the final outcome and how many retries were made.
+pub(crate) async fn send_with_retry(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+    policy: Option<&RetryPolicy>,
+    budget: Option<&RetryBudget>,
+    limiter: Option<&RateLimiter>,
+) -> (reqwest::Result<reqwest::Response>, u32) {
+    if let Some(budget) = budget {
+        budget.deposit();
+    }
+
+    let Some(policy) = policy else {
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        return (client.execute(request).await, 0);
+    };
+
+    let started = Instant::now();
+    let mut retries = 0;
+    let mut request = request;
+    loop {
+        // Requests with streaming bodies can't be replayed
+        let replay = request.try_clone();
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        let result = client.execute(request).await;
+
+        let next = match replay {
+            Some(replay) if retries + 1 < policy.max_attempts && is_transient(&result) => replay,
+            _ => return (result, retries),
+        };
+
+        let delay = policy.delay(retries);
+        if started.elapsed() + delay > MAX_RETRY_ELAPSED || budget.is_some_and(|budget| !budget.try_withdraw()) {
+            return (result, retries);
+        }
+
+        tokio::time::sleep(delay).await;
+        request = next;
+        retries += 1;
+    }
+}
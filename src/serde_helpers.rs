@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Deserializer};
+
+use crate::{value_as_bool, value_as_f64};
+
+/// Deserializes an optional number that the portal may send as a number or a
+/// string. Empty strings, placeholders such as `"--"` and anything else that
+/// isn't numeric become `None` rather than failing the whole struct.
+pub(crate) fn deserialize_lenient_f64<'de, D>(deserializer: D) -> std::result::Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(value_as_f64))
+}
+
+/// Deserializes an optional integer sent as a number or a string, with the
+/// same leniency as [`deserialize_lenient_f64`].
+pub(crate) fn deserialize_lenient_i64<'de, D>(deserializer: D) -> std::result::Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(value_as_f64).map(|n| n as i64))
+}
+
+/// Deserializes an optional flag sent as a boolean, `0`/`1` or their string
+/// forms. Anything else becomes `None`.
+pub(crate) fn deserialize_lenient_bool<'de, D>(deserializer: D) -> std::result::Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(value_as_bool))
+}
+
+/// Deserializes an optional `YYYY-MM-DD HH:MM:SS` timestamp. Empty or
+/// malformed strings and any other value, e.g. an epoch number, become `None`.
+pub(crate) fn deserialize_lenient_datetime<'de, D>(deserializer: D) -> std::result::Result<Option<NaiveDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value
+        .as_ref()
+        .and_then(|time| time.as_str())
+        .and_then(|time| NaiveDateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S").ok()))
+}
+
+/// Deserializes a typed struct from a raw result, taking the `obj` payload out
+/// of a full response envelope when given one.
+pub(crate) fn from_result_value<T: serde::de::DeserializeOwned>(value: &serde_json::Value) -> crate::Result<T> {
+    let payload = value.get("obj").filter(|obj| obj.is_object()).unwrap_or(value);
+    Ok(T::deserialize(payload)?)
+}
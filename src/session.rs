@@ -0,0 +1,149 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use cookie_store::{Cookie, CookieStore, RawCookie};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{Growatt, Result};
+
+/// Cookie storage for the HTTP client that, unlike reqwest's `Jar`, can be
+/// cleared so a fresh session doesn't reuse stale cookies.
+///
+/// Pass [`Growatt::cookie_jar`] to `reqwest::ClientBuilder::cookie_provider`
+/// when building a client for [`Growatt::with_client`].
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+}
+
+impl CookieJar {
+    pub(crate) fn clear(&self) {
+        self.store.write().unwrap().clear();
+    }
+
+    // Session cookies carry no expiry, so unlike `CookieStore::save_json` this
+    // keeps non-persistent cookies too
+    fn export(&self) -> Vec<Cookie<'static>> {
+        self.store.read().unwrap().iter_unexpired().cloned().collect()
+    }
+
+    // Cookies that expired since they were saved are dropped
+    fn import(&self, cookies: Vec<Cookie<'static>>) {
+        let cookies = cookies.into_iter().map(Ok::<_, std::convert::Infallible>);
+        let Ok(store) = CookieStore::from_cookies(cookies, false);
+        *self.store.write().unwrap() = store;
+    }
+}
+
+// What `save_session` writes to disk
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedSession {
+    cookies: Vec<Cookie<'static>>,
+    session_expiry: Option<DateTime<Utc>>,
+    token: Option<String>,
+}
+
+impl Growatt {
+    /// Writes the session cookies, expiry and token to `path` as JSON, so a
+    /// later process can pick the session up with [`Growatt::load_session`]
+    /// instead of logging in again. Credentials are not saved.
+    ///
+    /// The file grants access to the account until the session expires, so on
+    /// unix it is created readable by the owner only. It is written to a
+    /// temporary file next to `path` first and renamed into place, so a crash
+    /// never leaves a truncated session behind.
+    ///
+    /// A session a clone of this client logged in again for is saved too,
+    /// since clones share their cookies.
+    pub fn save_session(&self, path: &Path) -> Result<()> {
+        let saved = SavedSession {
+            cookies: self.cookies.export(),
+            session_expiry: self.session_expiry(),
+            token: self.get_token(),
+        };
+        write_private(path, &serde_json::to_vec_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    /// Creates a client with the default configuration and the session saved
+    /// at `path`, see [`Growatt::restore_session`].
+    pub fn load_session(path: &Path) -> Result<Growatt> {
+        Growatt::new().restore_session(path)
+    }
+
+    /// Restores a session saved with [`Growatt::save_session`]. If it hasn't
+    /// expired yet the client counts as logged in and requests go out without
+    /// a login; otherwise the next request logs in as usual.
+    pub fn restore_session(mut self, path: &Path) -> Result<Self> {
+        let saved: SavedSession = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        self.cookies.import(saved.cookies);
+        *self.recovered_session.lock().unwrap() = None;
+        self.session_expiry = saved.session_expiry;
+        self.token = saved.token;
+        self.is_logged_in = self.is_session_valid();
+        Ok(self)
+    }
+}
+
+// Writes to a temporary sibling that only the owner can read, then renames
+// it over `path`
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    // A leftover from a crash may have other permissions, which opening an
+    // existing file would keep
+    let _ = std::fs::remove_file(&temp_path);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let written = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers.filter_map(|header| {
+            let header = header.to_str().ok()?;
+            RawCookie::parse(header).ok().map(RawCookie::into_owned)
+        });
+        self.store.write().unwrap().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .store
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if header.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&header).ok()
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::PlantData;
+
+/// A fault code as reported by the device, e.g. `"102"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FaultCode(pub String);
+
+impl fmt::Display for FaultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatteryState {
+    Charging,
+    Discharging,
+    Idle,
+}
+
+/// The state of a plant at one point in time, for detecting changes between
+/// successive polls with [`PlantSnapshot::diff`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlantSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub online: bool,
+    /// Current output power in watts.
+    pub current_power: Option<f64>,
+    pub battery: Option<BatteryState>,
+    /// Faults active when the snapshot was taken.
+    pub faults: Vec<FaultCode>,
+}
+
+/// A meaningful difference between two [`PlantSnapshot`]s.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Change {
+    WentOffline,
+    CameOnline,
+    PowerChanged { from: Option<f64>, to: Option<f64> },
+    FaultAppeared(FaultCode),
+    FaultCleared(FaultCode),
+    BatteryStateChanged { from: Option<BatteryState>, to: Option<BatteryState> },
+}
+
+impl PlantSnapshot {
+    /// Relative change in power, against the larger of the two readings, below
+    /// which [`PlantSnapshot::diff`] ignores the difference as noise.
+    pub const POWER_CHANGE_RATIO: f64 = 0.05;
+
+    /// Takes a snapshot of the plant's power and online state. Battery state
+    /// and faults are left empty for the caller to fill in.
+    pub fn from_plant_data(data: &PlantData, online: bool) -> Self {
+        Self {
+            taken_at: Utc::now(),
+            online,
+            current_power: data.current_power,
+            battery: None,
+            faults: Vec::new(),
+        }
+    }
+
+    /// Lists what changed since `previous`, in a fixed order: online state,
+    /// power, battery state, then faults.
+    pub fn diff(&self, previous: &PlantSnapshot) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        match (previous.online, self.online) {
+            (true, false) => changes.push(Change::WentOffline),
+            (false, true) => changes.push(Change::CameOnline),
+            _ => {}
+        }
+
+        if power_changed(previous.current_power, self.current_power) {
+            changes.push(Change::PowerChanged {
+                from: previous.current_power,
+                to: self.current_power,
+            });
+        }
+
+        if previous.battery != self.battery {
+            changes.push(Change::BatteryStateChanged {
+                from: previous.battery,
+                to: self.battery,
+            });
+        }
+
+        changes.extend(
+            self.faults
+                .iter()
+                .filter(|fault| !previous.faults.contains(fault))
+                .cloned()
+                .map(Change::FaultAppeared),
+        );
+        changes.extend(
+            previous
+                .faults
+                .iter()
+                .filter(|fault| !self.faults.contains(fault))
+                .cloned()
+                .map(Change::FaultCleared),
+        );
+
+        changes
+    }
+}
+
+fn power_changed(from: Option<f64>, to: Option<f64>) -> bool {
+    match (from, to) {
+        (Some(from), Some(to)) => {
+            let scale = from.abs().max(to.abs());
+            scale > 0.0 && (to - from).abs() > scale * PlantSnapshot::POWER_CHANGE_RATIO
+        }
+        (None, None) => false,
+        _ => true,
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::{deserialize_lenient_f64, deserialize_lenient_i64, from_result_value};
+use crate::{Growatt, GrowattError, Result};
+
+/// Totals reported by `getStorageTotalData` for an SPF or SPA storage
+/// inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageTotalData {
+    /// PV energy generated today (kWh).
+    #[serde(rename = "epvToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_today: Option<f64>,
+    /// PV energy generated over the inverter's lifetime (kWh).
+    #[serde(rename = "epvTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_total: Option<f64>,
+    /// Energy charged into the battery today (kWh).
+    #[serde(rename = "eChargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_charge_today: Option<f64>,
+    /// Energy charged into the battery over the inverter's lifetime (kWh).
+    #[serde(rename = "eChargeTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_charge_total: Option<f64>,
+    /// Energy discharged from the battery today (kWh).
+    #[serde(rename = "eDischargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_discharge_today: Option<f64>,
+    /// Energy discharged from the battery over the inverter's lifetime (kWh).
+    #[serde(rename = "eDischargeTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_discharge_total: Option<f64>,
+    /// Energy consumed by the load today (kWh).
+    #[serde(rename = "useEnergyToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub use_energy_today: Option<f64>,
+    /// Energy consumed by the load over the inverter's lifetime (kWh).
+    #[serde(rename = "useEnergyTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub use_energy_total: Option<f64>,
+}
+
+/// Converts a raw total result, or a full response with the totals under `obj`.
+impl TryFrom<&serde_json::Value> for StorageTotalData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+/// Real-time status reported by `getStorageStatusData` for an SPF or SPA
+/// storage inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageStatusData {
+    /// Battery state of charge (%). The portal calls it `capacity`.
+    #[serde(default, alias = "capacity", deserialize_with = "deserialize_lenient_f64")]
+    pub soc: Option<f64>,
+    /// Battery charge power (W).
+    #[serde(rename = "chargePower", default, alias = "pCharge", deserialize_with = "deserialize_lenient_f64")]
+    pub charge_power: Option<f64>,
+    /// Battery discharge power (W).
+    #[serde(rename = "dischargePower", default, alias = "pDischarge", deserialize_with = "deserialize_lenient_f64")]
+    pub discharge_power: Option<f64>,
+    /// Power drawn by the load (W).
+    #[serde(rename = "loadPower", default, alias = "outPutPower", deserialize_with = "deserialize_lenient_f64")]
+    pub load_power: Option<f64>,
+    /// PV input power (W).
+    #[serde(default, alias = "panelPower", deserialize_with = "deserialize_lenient_f64")]
+    pub ppv: Option<f64>,
+    /// Battery voltage (V).
+    #[serde(rename = "vBat", default, deserialize_with = "deserialize_lenient_f64")]
+    pub v_bat: Option<f64>,
+    /// Raw run state code.
+    #[serde(default, deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+}
+
+/// Converts a raw status result, or a full response with the status under `obj`.
+impl TryFrom<&serde_json::Value> for StorageStatusData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+impl Growatt {
+    /// Fetches an SPF or SPA storage inverter's energy totals, the storage
+    /// counterpart of [`Growatt::get_mix_total`]. Use
+    /// [`Growatt::classify_plant_devices`] to find which devices are
+    /// [`DeviceType::Storage`](crate::DeviceType::Storage) or
+    /// [`DeviceType::Spa`](crate::DeviceType::Spa).
+    pub async fn get_storage_total(&mut self, plant_id: &str, storage_sn: &str) -> Result<StorageTotalData> {
+        let form = [("storageSn", storage_sn)];
+        let obj = self.post_plant_form("/panel/storage/getStorageTotalData", plant_id, &form).await?;
+        StorageTotalData::try_from(&obj)
+    }
+
+    /// Fetches an SPF or SPA storage inverter's real-time status.
+    pub async fn get_storage_status(&mut self, plant_id: &str, storage_sn: &str) -> Result<StorageStatusData> {
+        let form = [("storageSn", storage_sn)];
+        let obj = self.post_plant_form("/panel/storage/getStorageStatusData", plant_id, &form).await?;
+        StorageStatusData::try_from(&obj)
+    }
+}
@@ -1,8 +1,33 @@
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
 use std::env;
-use crate::{Growatt, Plant, PlantList, PlantData};
+use crate::chart::{day_power_curve, month_chart_series};
+use crate::devices::classify_devices;
+use crate::history::HistoryPage;
+use crate::mppt::parse_mppts;
+use crate::{parse_json_body, Growatt, GrowattError, RequestOptions};
+use crate::{Credentials, LoginResult, Role};
+use crate::{Address, PanelConfig, Plant, PlantData, PlantList, Tariff};
+use crate::{resolve_device_state, Device, DeviceEvent, DeviceInfo, DeviceState, DeviceType};
+use crate::{GridExchange, MixDevice, MixRunState, MixStatusData, MixTotalData};
+use crate::{Anchor, ChartDay, ChartMonth, ChartYear, GapFill};
+use crate::{ChargeSchedule, MixSettings, SetCommand, TimePeriod};
+use crate::{AlarmRule, BatteryCells, Comparison, FaultSeverity, MeterPhases, Weather};
+use crate::{BatteryState, Change, FaultCode, PlantSnapshot, PollSchedule};
+use crate::{AccountUnits, EnergyUnit, PowerUnit, TemperatureUnit};
+use crate::{ErrorKind, RequestMetrics, RetryBudget};
+
+// A client pointed at a mock server, with a session that looks valid
+fn logged_in_client(base_url: &str) -> Growatt {
+    Growatt::test_logged_in(base_url)
+}
+
+// A mock that answers with a JSON content type, as the portal does
+fn json_mock(server: &mut mockito::ServerGuard, method: &str, path: &str) -> mockito::Mock {
+    server.mock(method, path).with_header("content-type", "application/json")
+}
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_new_client() {
     let client = Growatt::new();
     assert_eq!(client.base_url, "https://server.growatt.com");
@@ -13,12 +38,24 @@ fn test_new_client() {
     assert!(client.token.is_none());
     // Check session duration is 30 minutes
     assert_eq!(client.session_duration, Duration::minutes(30));
+    assert!(client.read_pact);
+    assert_eq!(client.online_threshold, Duration::minutes(20));
+    assert_eq!(client.max_response_size, crate::DEFAULT_MAX_RESPONSE_SIZE);
 }
 
 #[test]
 fn test_with_alternate_url() {
     let client = Growatt::new().with_alternate_url();
     assert_eq!(client.base_url, "https://openapi.growatt.com");
+    assert_eq!(client.api_mode(), crate::ApiMode::OpenApiV1);
+}
+
+#[tokio::test]
+async fn test_alternate_url_without_token() {
+    // The v1 endpoints are selected, but can't be called without a token
+    let mut client = Growatt::new().with_alternate_url();
+    assert!(matches!(client.get_plants().await, Err(GrowattError::InvalidConfig(_))));
+    assert!(matches!(client.get_plant("1").await, Err(GrowattError::InvalidConfig(_))));
 }
 
 #[test]
@@ -27,6 +64,49 @@ fn test_with_session_duration() {
     assert_eq!(client.session_duration, Duration::minutes(60));
 }
 
+#[test]
+fn test_with_online_threshold() {
+    let client = Growatt::new().with_online_threshold(45);
+    assert_eq!(client.online_threshold, Duration::minutes(45));
+}
+
+#[test]
+fn test_with_retry_budget() {
+    let client = Growatt::new();
+    assert!(client.retry_budget().is_none());
+
+    let client = Growatt::new().with_retry_budget(0.2);
+    assert_eq!(client.retry_budget().unwrap().ratio(), 0.2);
+}
+
+#[test]
+fn test_retry_budget_runs_dry() {
+    let budget = RetryBudget::new(0.5);
+
+    // The reserve allows a burst of retries, then the budget is exhausted
+    let burst = (0..20).take_while(|_| budget.try_withdraw()).count();
+    assert_eq!(burst, 10);
+    assert!(!budget.try_withdraw());
+
+    // Two requests earn one retry
+    budget.deposit();
+    assert!(!budget.try_withdraw());
+    budget.deposit();
+    assert!(budget.try_withdraw());
+}
+
+#[test]
+fn test_with_max_response_size() {
+    let client = Growatt::new().with_max_response_size(1024);
+    assert_eq!(client.max_response_size, 1024);
+}
+
+#[test]
+fn test_with_read_pact() {
+    let client = Growatt::new().with_read_pact(false);
+    assert!(!client.read_pact);
+}
+
 #[test]
 fn test_hash_password() {
     let client = Growatt::new();
@@ -52,8 +132,64 @@ fn test_is_session_valid() {
     assert!(!client.is_session_valid());
 }
 
+// Serializes the tests that change the process environment
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// Sets or removes an environment variable, returning its previous value
+fn replace_env(key: &str, value: Option<&str>) -> Option<String> {
+    let original = env::var(key).ok();
+    match value {
+        Some(value) => env::set_var(key, value),
+        None => env::remove_var(key),
+    }
+    original
+}
+
+#[tokio::test]
+async fn test_connect_from_env() {
+    let _env = ENV_LOCK.lock().await;
+    let mut server = mockito::Server::new_async().await;
+    let login = server
+        .mock("POST", "/login")
+        .match_body(mockito::Matcher::UrlEncoded("account".into(), "user".into()))
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _unknown = server
+        .mock("POST", "/login")
+        .match_body(mockito::Matcher::UrlEncoded("account".into(), "nobody".into()))
+        .with_body(r#"{"result": 0, "msg": "501"}"#)
+        .create_async()
+        .await;
+
+    let original_base_url = replace_env("GROWATT_BASE_URL", Some(&server.url()));
+    let original_username = replace_env("GROWATT_USERNAME", Some("user"));
+    let original_password = replace_env("GROWATT_PASSWORD", None);
+
+    // A missing credential fails without contacting the server
+    let missing = Growatt::connect_from_env().await;
+
+    replace_env("GROWATT_PASSWORD", Some("pass"));
+    let connected = Growatt::connect_from_env().await;
+
+    replace_env("GROWATT_USERNAME", Some("nobody"));
+    let unknown = Growatt::connect_from_env().await;
+
+    replace_env("GROWATT_BASE_URL", original_base_url.as_deref());
+    replace_env("GROWATT_USERNAME", original_username.as_deref());
+    replace_env("GROWATT_PASSWORD", original_password.as_deref());
+
+    assert!(matches!(missing, Err(GrowattError::NotLoggedIn)));
+    assert!(connected.unwrap().is_logged_in());
+    assert!(matches!(unknown, Err(GrowattError::AccountNotFound)));
+    login.assert_async().await;
+}
+
 #[test]
 fn test_from_env() {
+    let _env = ENV_LOCK.blocking_lock();
+
     // Backup existing env vars if any
     let original_username = env::var("GROWATT_USERNAME").ok();
     let original_password = env::var("GROWATT_PASSWORD").ok();
@@ -114,6 +250,36 @@ fn test_plant_structs() {
     assert_eq!(plant.is_share, Some(false));
 }
 
+#[test]
+fn test_plant_is_share_accepts_flag_forms() {
+    let cases = [
+        (serde_json::json!(true), Some(true)),
+        (serde_json::json!(false), Some(false)),
+        (serde_json::json!(1), Some(true)),
+        (serde_json::json!(0), Some(false)),
+        (serde_json::json!("1"), Some(true)),
+        (serde_json::json!("0"), Some(false)),
+        (serde_json::json!("true"), Some(true)),
+        (serde_json::json!("False"), Some(false)),
+        (serde_json::json!(""), None),
+        (serde_json::Value::Null, None),
+    ];
+
+    for (is_share, expected) in cases {
+        let plant: Plant = serde_json::from_value(serde_json::json!({
+            "id": "123",
+            "plantName": "Test Plant",
+            "isShare": is_share
+        }))
+        .unwrap();
+        assert_eq!(plant.is_share, expected, "isShare = {}", is_share);
+    }
+
+    // Missing entirely
+    let plant: Plant = serde_json::from_value(serde_json::json!({"id": "123", "plantName": "Test Plant"})).unwrap();
+    assert_eq!(plant.is_share, None);
+}
+
 #[test]
 fn test_plant_list() {
     // Test PlantList wrapper
@@ -123,6 +289,7 @@ fn test_plant_list() {
         plant_address: Some("Address 1".to_string()),
         plant_watts: Some(1000.0),
         is_share: Some(false),
+        shared_by: None,
     };
     
     let plant2 = Plant {
@@ -131,6 +298,7 @@ fn test_plant_list() {
         plant_address: Some("Address 2".to_string()),
         plant_watts: Some(2000.0),
         is_share: Some(true),
+        shared_by: Some("owner".to_string()),
     };
     
     let plant_list = PlantList(vec![plant1, plant2]);
@@ -138,6 +306,33 @@ fn test_plant_list() {
     assert_eq!(plant_list.0.len(), 2);
     assert_eq!(plant_list.0[0].plant_id, "1");
     assert_eq!(plant_list.0[1].plant_id, "2");
+
+    let shared = plant_list.shared();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].shared_by.as_deref(), Some("owner"));
+    assert_eq!(plant_list.owned()[0].plant_id, "1");
+}
+
+#[tokio::test]
+async fn test_get_plants_empty_account() {
+    let mut server = mockito::Server::new_async().await;
+    let list = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(client.get_plants().await.unwrap().0.is_empty());
+    list.remove_async().await;
+
+    // Without a session the portal serves its login page instead
+    let _login_page = server
+        .mock("POST", "/index/getPlantListTitle")
+        .with_header("content-type", "text/html;charset=UTF-8")
+        .with_body("<!DOCTYPE html><html><body>login</body></html>")
+        .create_async()
+        .await;
+    assert!(matches!(client.get_plants().await, Err(GrowattError::SessionExpired)));
 }
 
 #[test]
@@ -160,4 +355,2894 @@ fn test_plant_data_struct() {
     assert_eq!(plant_data.today_energy, Some(23.5));
     assert_eq!(plant_data.total_energy, Some(1234.5));
     assert_eq!(plant_data.current_power, Some(4500.0));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_plant_data_lenient_numbers() {
+    let plant_data: PlantData = serde_json::from_str(r#"{
+        "plantName": "Test Plant",
+        "capacity": "5000.0",
+        "todayEnergy": "",
+        "totalEnergy": "--",
+        "currentPower": "null",
+        "ratedPower": " 4600 ",
+        "nominalPower": "NaN"
+    }"#).unwrap();
+
+    assert_eq!(plant_data.capacity, Some(5000.0));
+    assert_eq!(plant_data.today_energy, None);
+    assert_eq!(plant_data.total_energy, None);
+    assert_eq!(plant_data.current_power, None);
+    assert_eq!(plant_data.rated_power, Some(4600.0));
+    assert_eq!(plant_data.nominal_power, None);
+
+    // Missing fields are None too
+    let plant_data: PlantData = serde_json::from_str(r#"{"capacity": null}"#).unwrap();
+    assert_eq!(plant_data.capacity, None);
+    assert_eq!(plant_data.today_energy, None);
+
+    let plants: Vec<Plant> = serde_json::from_str(r#"[
+        {"id": "1", "name": "A", "plantPower": "2000"},
+        {"id": "2", "name": "B", "plantPower": "--"},
+        {"id": "3", "name": "C", "plantPower": 1500.5},
+        {"id": "4", "name": "D", "plantPower": ""}
+    ]"#).unwrap();
+    let watts: Vec<Option<f64>> = plants.iter().map(|plant| plant.plant_watts).collect();
+    assert_eq!(watts, vec![Some(2000.0), None, Some(1500.5), None]);
+}
+
+#[test]
+fn test_plant_data_is_clipping() {
+    let mut plant_data: PlantData = serde_json::from_str(r#"{
+        "currentPower": 4950.0,
+        "ratedPower": 5000.0
+    }"#).unwrap();
+    assert_eq!(plant_data.is_clipping(), Some(true));
+
+    plant_data.current_power = Some(3000.0);
+    assert_eq!(plant_data.is_clipping(), Some(false));
+
+    // Missing rated power means we cannot tell
+    plant_data.rated_power = None;
+    assert_eq!(plant_data.is_clipping(), None);
+}
+
+#[test]
+fn test_chart_anchor_formatting() {
+    let date = NaiveDate::from_ymd_opt(2025, 4, 6).unwrap();
+
+    assert_eq!(Anchor::from(ChartDay(date)).to_param(), "2025-04-06");
+    assert_eq!(Anchor::from(ChartMonth(date)).to_param(), "2025-04");
+    assert_eq!(Anchor::from(ChartYear(2025)).to_param(), "2025");
+}
+
+#[test]
+fn test_mix_settings_from_value() {
+    let settings = serde_json::json!({
+        "chargePowerCommand": "100",
+        "wchargeSOCLowLimit2": "95",
+        "acChargeEnable": "1",
+        "forcedChargeTimeStart1": "1:30",
+        "forcedChargeTimeStop1": "5:0",
+        "forcedChargeStopSwitch1": "1",
+        "forcedChargeTimeStart3": "0:0",
+        "forcedChargeTimeStop3": "0:0",
+        "forcedChargeStopSwitch3": "0",
+        "forcedDischargeTimeStart2": "17:0",
+        "forcedDischargeTimeStop2": "20:30",
+        "forcedDischargeStopSwitch2": "1",
+        "activeRate": 80,
+        "onOff": "1"
+    });
+
+    let settings = MixSettings::from_value(&settings);
+
+    assert_eq!(settings.charge_power_rate, Some(100.0));
+    assert_eq!(settings.charge_stop_soc, Some(95.0));
+    assert_eq!(settings.ac_charge_enabled, Some(true));
+    // Slots keep their position, whether disabled or missing
+    let first = settings.charge_periods[0].as_ref().unwrap();
+    assert_eq!(first.start, NaiveTime::from_hms_opt(1, 30, 0).unwrap());
+    assert_eq!(first.end, NaiveTime::from_hms_opt(5, 0, 0).unwrap());
+    assert!(first.enabled);
+    assert!(settings.charge_periods[1].is_none());
+    assert!(!settings.charge_periods[2].as_ref().unwrap().enabled);
+    assert!(settings.discharge_periods[0].is_none());
+    assert_eq!(settings.discharge_periods[1].as_ref().unwrap().end, NaiveTime::from_hms_opt(20, 30, 0).unwrap());
+    assert!(settings.discharge_periods[2].is_none());
+    assert_eq!(settings.active_power_rate, Some(80.0));
+    assert_eq!(settings.on_off, Some(true));
+}
+
+#[test]
+fn test_set_command_params() {
+    let command = SetCommand::active_power_limit(100).unwrap().with_action("maxSet");
+
+    assert_eq!(command.action(), "maxSet");
+    assert_eq!(command.param_type(), "pv_active_p_rate");
+    assert_eq!(command.params(), ["100".to_string()]);
+
+    // Out of range percentages are rejected, not capped
+    assert!(matches!(SetCommand::active_power_limit(150), Err(GrowattError::InvalidInput(_))));
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_set_param_bulk_concurrency() {
+    let mut server = mockito::Server::new_async().await;
+    let _set = json_mock(&mut server, "POST", "/tcpSet.do")
+        .with_chunked_body(|w| {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            w.write_all(br#"{"success": true}"#)
+        })
+        .expect(3)
+        .create_async()
+        .await;
+
+    // One at a time, the writes queue up behind each other
+    let mut client = logged_in_client(&server.url()).with_plant_concurrency(1);
+    let devices = ["MIX1", "MIX2", "MIX3"].map(String::from);
+    let started = std::time::Instant::now();
+    let results = client
+        .set_param_bulk(&devices, SetCommand::active_power_limit(80).unwrap())
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() >= std::time::Duration::from_millis(600));
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|(_, result)| matches!(result, Ok(true))));
+}
+
+#[test]
+fn test_parse_json_body_detects_login_page() {
+    let html = "\n<!DOCTYPE html>\n<html><head><title>Login</title></head></html>";
+    assert!(matches!(
+        parse_json_body(Some("text/html;charset=UTF-8"), html),
+        Err(GrowattError::SessionExpired)
+    ));
+    // The body alone is enough, whatever the content type claims
+    assert!(matches!(
+        parse_json_body(Some("application/json"), "<html></html>"),
+        Err(GrowattError::SessionExpired)
+    ));
+
+    // JSON mislabelled as HTML is still parsed
+    let json = parse_json_body(Some("text/html"), r#"{"result": 1}"#).unwrap();
+    assert_eq!(json["result"], 1);
+
+    // Malformed JSON stays a JSON error
+    assert!(matches!(
+        parse_json_body(Some("application/json"), "{"),
+        Err(GrowattError::JsonError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_expired_session_relogin() {
+    let mut server = mockito::Server::new_async().await;
+    let login_page = server
+        .mock("POST", "/device/getMAXList")
+        .with_header("content-type", "text/html;charset=UTF-8")
+        .with_body("<!DOCTYPE html><html><body>login</body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let login = server
+        .mock("POST", "/login")
+        .with_body(r#"{"result": 1, "token": "fresh", "user": {"id": 7}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _devices = json_mock(&mut server, "POST", "/device/getMAXList")
+        .with_body(r#"{"result": 1, "obj": {"datas": [{"deviceSn": "MAX1"}]}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.username = Some("user".to_string());
+    client.password = Some("pass".to_string());
+    client.session_expiry = Some(Utc::now() + Duration::minutes(1));
+    client.token = Some("stale".to_string());
+
+    let devices = client.get_device_list("1").await.unwrap();
+    assert_eq!(devices["obj"]["datas"][0]["deviceSn"], "MAX1");
+    login_page.assert_async().await;
+    login.assert_async().await;
+    assert!(client.is_logged_in());
+    // The client reports the recovered session, not the expired one
+    assert!(client.time_until_expiry().unwrap() > Duration::minutes(25));
+    assert_eq!(client.get_token().as_deref(), Some("fresh"));
+
+    // and saves it rather than the expired one
+    let path = env::temp_dir().join(format!("growatt-recovered-{}.json", std::process::id()));
+    client.save_session(&path).unwrap();
+    let restored = Growatt::load_session(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(restored.time_until_expiry().unwrap() > Duration::minutes(25));
+    assert_eq!(restored.get_token().as_deref(), Some("fresh"));
+
+    // Without credentials the expiry is reported as is
+    let mut client = logged_in_client(&server.url());
+    server.reset();
+    let _login_page = server
+        .mock("POST", "/device/getMAXList")
+        .with_header("content-type", "text/html")
+        .with_body("<html></html>")
+        .create_async()
+        .await;
+    assert!(matches!(client.get_device_list("1").await, Err(GrowattError::SessionExpired)));
+    assert!(!client.is_logged_in());
+}
+
+#[tokio::test]
+async fn test_expired_session_relogin_on_fetch() {
+    let mut server = mockito::Server::new_async().await;
+    let login_page = server
+        .mock("POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_header("content-type", "text/html;charset=UTF-8")
+        .with_body("<!DOCTYPE html><html><body>login</body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let login = server
+        .mock("POST", "/login")
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"result": 1, "obj": {"plantId": "1", "todayEnergy": "5.5"}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.username = Some("user".to_string());
+    client.password = Some("pass".to_string());
+
+    let plant = client.get_plant("1").await.unwrap();
+    assert_eq!(plant.today_energy, Some(5.5));
+    login_page.assert_async().await;
+    login.assert_async().await;
+
+    // The request is retried once; a second login page is reported as is
+    server.reset();
+    let login_page = server
+        .mock("POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_header("content-type", "text/html")
+        .with_body("<html></html>")
+        .expect(2)
+        .create_async()
+        .await;
+    let login = server
+        .mock("POST", "/login")
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    assert!(matches!(client.get_plant("1").await, Err(GrowattError::SessionExpired)));
+    login_page.assert_async().await;
+    login.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_env_sensors() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _sensors = json_mock(&mut server, "POST", "/device/getEnvList")
+        .match_body(Matcher::UrlEncoded("plantId".into(), "1".into()))
+        .with_body(r#"{"result": 1, "obj": {"datas": [
+            {"deviceSn": "ENV1", "alias": "Roof", "datalogSn": "DL1", "deviceStatus": "1",
+             "lastUpdateTime": "2025-04-26 12:05:00", "envTemp": "28.5", "envHumidity": 61,
+             "radiant": "812", "windSpeed": "", "panelTemp": "--"},
+            {"alias": "no serial"}
+        ]}}"#)
+        .create_async()
+        .await;
+    let _empty = json_mock(&mut server, "POST", "/device/getEnvList")
+        .match_body(Matcher::UrlEncoded("plantId".into(), "2".into()))
+        .with_body(r#"{"result": 1, "obj": {"datas": [], "pages": 0}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let sensors = client.get_env_sensors("1").await.unwrap();
+    assert_eq!(sensors.len(), 1);
+    let sensor = &sensors[0];
+    assert_eq!(sensor.device_sn, "ENV1");
+    assert_eq!(sensor.datalogger_sn.as_deref(), Some("DL1"));
+    assert_eq!(sensor.status, Some(1));
+    assert_eq!(sensor.temperature, Some(28.5));
+    assert_eq!(sensor.humidity, Some(61.0));
+    assert_eq!(sensor.irradiance, Some(812.0));
+    assert_eq!(sensor.wind_speed, None);
+    assert_eq!(sensor.panel_temperature, None);
+
+    // A plant without sensors is not an error
+    assert!(client.get_env_sensors("2").await.unwrap().is_empty());
+}
+
+#[test]
+fn test_weather_from_value() {
+    let obj = serde_json::json!({
+        "city": "Bangkok",
+        "data": {
+            "HeWeather6": [{
+                "now": { "tmp": "31", "cond_txt": "Sunny", "hum": "65" },
+                "daily_forecast": [
+                    { "date": "2025-04-26", "cond_txt_d": "Cloudy", "tmp_max": "34", "tmp_min": "26", "irradiance": "612" },
+                    { "date": "2025-04-27", "cond_txt_d": "Shower Rain", "tmp_max": "32", "tmp_min": "25" }
+                ]
+            }]
+        }
+    });
+
+    let weather = Weather::from_value(&obj).unwrap();
+
+    assert_eq!(weather.temperature, Some(31.0));
+    assert_eq!(weather.condition.as_deref(), Some("Sunny"));
+    assert_eq!(weather.humidity, Some(65.0));
+    assert_eq!(weather.forecast.len(), 2);
+    assert_eq!(weather.forecast[1].date, NaiveDate::from_ymd_opt(2025, 4, 27).unwrap());
+    assert_eq!(weather.forecast[1].temp_low, Some(25.0));
+    assert_eq!(weather.forecast[0].irradiance, Some(612.0));
+    assert_eq!(weather.forecast[1].irradiance, None);
+}
+
+#[test]
+fn test_mix_total_environmental_impact() {
+    let json_data = r#"{
+        "epvToday": "12.3",
+        "epvTotal": 4567.8,
+        "co2Reduction": "4554.1",
+        "coalReduction": 1826.5,
+        "treeNumber": "--"
+    }"#;
+
+    let mix_total: MixTotalData = serde_json::from_str(json_data).unwrap();
+    assert_eq!(mix_total.epv_today, Some(12.3));
+    assert_eq!(mix_total.epv_total, Some(4567.8));
+
+    let impact = mix_total.environmental_impact();
+    assert_eq!(impact.co2_reduction, Some(4554.1));
+    assert_eq!(impact.coal_reduction, Some(1826.5));
+    assert_eq!(impact.trees_planted, None);
+}
+
+#[test]
+fn test_try_from_result_value() {
+    let envelope = serde_json::json!({"result": 1, "obj": {"epvToday": "12.3"}});
+    assert_eq!(MixTotalData::try_from(&envelope).unwrap().epv_today, Some(12.3));
+
+    let obj = serde_json::json!({"epvToday": 4});
+    assert_eq!(MixTotalData::try_from(&obj).unwrap().epv_today, Some(4.0));
+
+    let device = Device::try_from(&serde_json::json!({"deviceSn": "INV1", "pac": "150"})).unwrap();
+    assert_eq!(device.power, Some(150.0));
+    assert!(Device::try_from(&serde_json::json!({"alias": "no serial"})).is_err());
+}
+
+#[test]
+fn test_mix_total_grid_exchange() {
+    let mix_total: MixTotalData = serde_json::from_value(serde_json::json!({
+        "eToGridTotal": "812.4",
+        "eToUserTotal": 1290.7
+    }))
+    .unwrap();
+
+    let exchange = mix_total.grid_exchange();
+    assert_eq!(exchange.exported_kwh, Some(812.4));
+    assert_eq!(exchange.imported_kwh, Some(1290.7));
+    assert_eq!(MixTotalData::default().grid_exchange(), GridExchange::default());
+}
+
+#[test]
+fn test_mix_total_consumption_ratios() {
+    let mix_total: MixTotalData = serde_json::from_value(serde_json::json!({
+        "epvTotal": "1000",
+        "eToGridTotal": "250",
+        "elocalLoadTotal": "1200",
+        "eToUserTotal": "300"
+    }))
+    .unwrap();
+    assert_eq!(mix_total.self_consumption_ratio(), Some(0.75));
+    assert_eq!(mix_total.grid_independence_ratio(), Some(0.75));
+
+    // Missing counters and nothing generated
+    let mix_total: MixTotalData = serde_json::from_value(serde_json::json!({
+        "epvTotal": 0, "eToGridTotal": 0, "elocalLoadTotal": 500
+    }))
+    .unwrap();
+    assert_eq!(mix_total.self_consumption_ratio(), None);
+    assert_eq!(mix_total.grid_independence_ratio(), None);
+}
+
+#[tokio::test]
+async fn test_reconnect_without_credentials() {
+    let mut client = Growatt::new();
+    client.is_logged_in = true;
+    client.session_expiry = Some(Utc::now() + Duration::hours(1));
+
+    assert!(matches!(client.reconnect().await, Err(GrowattError::NotLoggedIn)));
+    // Nothing is reset when there is nothing to reconnect with
+    assert!(client.is_logged_in);
+}
+
+#[tokio::test]
+async fn test_reconnect_drops_stale_cookies() {
+    use mockito::Matcher;
+    use reqwest::cookie::CookieStore;
+
+    let mut server = mockito::Server::new_async().await;
+    let login = server
+        .mock("POST", "/login")
+        .match_header("cookie", Matcher::Missing)
+        .with_header("set-cookie", "JSESSIONID=fresh; Path=/")
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.username = Some("user".to_string());
+    client.password = Some("pass".to_string());
+    let url = reqwest::Url::parse(&server.url()).unwrap();
+    let header = reqwest::header::HeaderValue::from_static("JSESSIONID=stale; Path=/");
+    client.cookies.set_cookies(&mut std::iter::once(&header), &url);
+
+    assert!(client.relogin().await.unwrap());
+    login.assert_async().await;
+    assert!(client.is_logged_in());
+    let cookies = client.cookies.cookies(&url).unwrap();
+    assert_eq!(cookies.to_str().unwrap(), "JSESSIONID=fresh");
+}
+
+#[tokio::test]
+async fn test_with_client() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let login = server
+        .mock("POST", "/login")
+        .match_header("x-app", "dashboard")
+        .with_header("set-cookie", "JSESSIONID=abc; Path=/")
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .create_async()
+        .await;
+    let plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .match_header("x-app", "dashboard")
+        .match_header("cookie", Matcher::Regex("JSESSIONID=abc".into()))
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let growatt = Growatt::new().with_base_url(&server.url());
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-app", reqwest::header::HeaderValue::from_static("dashboard"));
+    let http = reqwest::Client::builder()
+        .cookie_provider(growatt.cookie_jar())
+        .default_headers(headers)
+        .build()
+        .unwrap();
+
+    // Connection settings made afterwards must not replace the supplied client
+    let mut client = growatt.with_client(http).with_user_agent("other/1.0");
+    assert!(client.login("user", "pass").await.unwrap());
+    assert_eq!(client.get_plants().await.unwrap().0.len(), 1);
+    login.assert_async().await;
+    plants.assert_async().await;
+}
+
+#[test]
+fn test_reset_session_clears_cookies() {
+    use reqwest::cookie::CookieStore;
+
+    let mut client = Growatt::new();
+    let url = reqwest::Url::parse("https://server.growatt.com/index").unwrap();
+    let header = reqwest::header::HeaderValue::from_static("JSESSIONID=abc123; Path=/");
+    client.cookies.set_cookies(&mut std::iter::once(&header), &url);
+    client.is_logged_in = true;
+    client.token = Some("token".to_string());
+
+    assert!(client.cookies.cookies(&url).is_some());
+
+    client.reset_session();
+
+    assert!(client.cookies.cookies(&url).is_none());
+    assert!(!client.is_logged_in);
+    assert!(client.session_expiry.is_none());
+    assert!(client.token.is_none());
+}
+
+#[test]
+fn test_fault_severity_mapping() {
+    assert_eq!(FaultSeverity::All.fault_type(), 0);
+    assert_eq!(FaultSeverity::Fault.fault_type(), 1);
+    assert_eq!(FaultSeverity::Warning.fault_type(), 2);
+    assert_eq!(FaultSeverity::Info.fault_type(), 3);
+    assert_eq!(FaultSeverity::Fault.device_flag(), 0);
+}
+
+#[tokio::test]
+async fn test_get_all_fault_logs() {
+    use chrono::{Local, TimeZone};
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _page1 = json_mock(&mut server, "POST", "/log/getNewPlantFaultLog")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("toPageNum".into(), "1".into()),
+            Matcher::UrlEncoded("type".into(), "1".into()),
+        ]))
+        .with_body(r#"{"result": 1, "obj": {"count": 3, "datas": [
+            {"deviceSn": "MIX1", "time": "2025-04-26 10:15:00", "eventId": 302, "eventName": "No AC connection",
+             "eventSolution": "Check the grid", "type": "1"},
+            {"sn": "MIX1", "time": "2025-04-26 11:00:00", "eventId": "303", "eventName": "NE abnormal", "eventSolution": ""}
+        ]}}"#)
+        .create_async()
+        .await;
+    let _page2 = json_mock(&mut server, "POST", "/log/getNewPlantFaultLog")
+        .match_body(Matcher::UrlEncoded("toPageNum".into(), "2".into()))
+        .with_body(r#"{"result": 1, "obj": {"count": 3, "datas": [
+            {"deviceSn": "MIX2", "time": "2025-04-26 12:30:00", "eventId": "117", "eventName": "Relay fault"}
+        ]}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let logs = client
+        .get_all_fault_logs("1", Some("2025-04-26"), "", FaultSeverity::Fault)
+        .await
+        .unwrap();
+
+    assert_eq!(logs.len(), 3);
+    assert_eq!(logs[0].device_sn, "MIX1");
+    assert_eq!(logs[0].time, Local.with_ymd_and_hms(2025, 4, 26, 10, 15, 0).unwrap());
+    assert_eq!(logs[0].event_id, "302");
+    assert_eq!(logs[0].solution.as_deref(), Some("Check the grid"));
+    assert_eq!(logs[0].fault_type, Some(1));
+    assert_eq!(logs[1].device_sn, "MIX1");
+    assert_eq!(logs[1].solution, None);
+    assert_eq!(logs[2].event_name, "Relay fault");
+
+    // A malformed time fails the page rather than being guessed
+    let bad = serde_json::json!({"obj": {"count": 1, "datas": [{"time": "26/04/2025"}]}});
+    match crate::FaultLogPage::from_response(&bad, 1) {
+        Err(GrowattError::InvalidResponse(message)) => assert!(message.contains("26/04/2025")),
+        other => panic!("expected InvalidResponse, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_get_plant_total() {
+    let mut server = mockito::Server::new_async().await;
+    let _total = json_mock(&mut server, "POST", "/panel/getPlantTotalData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {
+            "eToday": "12.3", "eMonth": "245.6", "eTotal": "10234.5",
+            "mToday": "3.69", "mTotal": "3070.35", "moneyUnit": "euro",
+            "co2": "10203.8", "coal": "4093.8", "tree": ""
+        }}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let total = client.get_plant_total("1").await.unwrap();
+
+    assert_eq!(total.energy_today, Some(12.3));
+    assert_eq!(total.energy_month, Some(245.6));
+    assert_eq!(total.energy_total, Some(10234.5));
+    assert_eq!(total.revenue_today, Some(3.69));
+    assert_eq!(total.revenue_total, Some(3070.35));
+    assert_eq!(total.currency.as_deref(), Some("euro"));
+
+    let impact = total.environmental_impact();
+    assert_eq!(impact.co2_reduction, Some(10203.8));
+    assert_eq!(impact.coal_reduction, Some(4093.8));
+    assert_eq!(impact.trees_planted, None);
+}
+
+#[test]
+fn test_tariff_from_value() {
+    let tariff = Tariff::from_value(&serde_json::json!({
+        "formulaMoney": "0.25",
+        "formulaMoneyUnitId": "euro",
+        "plantMoneyText": "0.25 (euro/kWh)"
+    }));
+    assert_eq!(tariff.price_per_kwh, Some(0.25));
+    assert_eq!(tariff.currency.as_deref(), Some("euro"));
+    assert_eq!(tariff.feed_in_rate, None);
+
+    // Currency falls back to the unit in the money text
+    let tariff = Tariff::from_value(&serde_json::json!({
+        "formulaMoney": 4.5,
+        "plantMoneyText": "4.5 (THB/kWh)",
+        "feedInPrice": "2.2"
+    }));
+    assert_eq!(tariff.currency.as_deref(), Some("THB"));
+    assert_eq!(tariff.feed_in_rate, Some(2.2));
+}
+
+#[test]
+fn test_history_page_from_value() {
+    let page = HistoryPage::from_value(&serde_json::json!({
+        "haveNext": true,
+        "start": 2,
+        "datas": [
+            { "time": "2025-04-26 10:00:00", "pac": "1500.5", "ppv": 1620 },
+            { "time": "2025-04-26 10:05:00", "pac": "--" },
+            { "pac": 10 }
+        ]
+    }));
+
+    // The record without a time is skipped
+    assert_eq!(page.samples.len(), 2);
+    assert_eq!(page.samples[0].pac, Some(1500.5));
+    assert_eq!(page.samples[0].ppv, Some(1620.0));
+    assert_eq!(page.samples[1].pac, None);
+    assert_eq!(page.next_start, Some(2));
+
+    // A page claiming more data without carrying any ends the day
+    let page = HistoryPage::from_value(&serde_json::json!({ "haveNext": true, "start": 0, "datas": [] }));
+    assert!(page.samples.is_empty());
+    assert_eq!(page.next_start, None);
+}
+
+#[tokio::test]
+async fn test_stream_device_history_inverted_range() {
+    use futures::StreamExt;
+
+    let mut client = logged_in_client("http://127.0.0.1:9");
+    let start = NaiveDate::from_ymd_opt(2025, 4, 27).unwrap();
+    let end = NaiveDate::from_ymd_opt(2025, 4, 26).unwrap();
+
+    let results: Vec<_> = client.stream_device_history("MAX1", start, end).collect().await;
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0], Err(GrowattError::InvalidInput(_))));
+}
+
+#[test]
+fn test_is_device_reporting() {
+    use crate::devices::is_device_reporting;
+
+    let now = NaiveDate::from_ymd_opt(2025, 4, 26).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    let threshold = Duration::minutes(20);
+
+    let recent = serde_json::json!({ "lost": false, "status": "1", "lastUpdateTime": "2025-04-26 11:55:00" });
+    assert!(is_device_reporting(&recent, now, threshold));
+
+    let stale = serde_json::json!({ "lost": false, "status": "1", "lastUpdateTime": "2025-04-26 10:00:00" });
+    assert!(!is_device_reporting(&stale, now, threshold));
+
+    let lost = serde_json::json!({ "lost": true, "status": "1", "lastUpdateTime": "2025-04-26 11:55:00" });
+    assert!(!is_device_reporting(&lost, now, threshold));
+
+    let faulted = serde_json::json!({ "lost": "false", "status": 3 });
+    assert!(!is_device_reporting(&faulted, now, threshold));
+
+    // Judged by resolve_device_state, whichever key carries the status
+    let offline = serde_json::json!({ "deviceStatus": "-1" });
+    assert!(!is_device_reporting(&offline, now, threshold));
+    let standby = serde_json::json!({ "deviceStatus": "0", "lastUpdateTime": "2025-04-26 11:55:00" });
+    assert!(is_device_reporting(&standby, now, threshold));
+}
+
+#[test]
+fn test_plant_local_now() {
+    use crate::devices::plant_local_now;
+
+    // The plant's offset decides, wherever this machine is
+    let utc = Utc::now().naive_utc();
+    let bangkok = plant_local_now(&serde_json::json!({ "timezone": "7" }));
+    assert!((bangkok - utc - Duration::hours(7)).num_seconds().abs() < 60);
+    let kolkata = plant_local_now(&serde_json::json!({ "timezone": 5.5 }));
+    assert!((kolkata - utc - Duration::minutes(330)).num_seconds().abs() < 60);
+}
+
+#[test]
+fn test_plant_data_underperforming() {
+    let mut plant_data: PlantData = serde_json::from_str(r#"{
+        "currentPower": 1000.0,
+        "nominalPower": "5000"
+    }"#).unwrap();
+    assert_eq!(plant_data.nominal_power, Some(5000.0));
+    assert_eq!(plant_data.underperforming(0.5), Some(true));
+    assert_eq!(plant_data.underperforming(0.1), Some(false));
+
+    // Near-zero output is treated as night time
+    plant_data.current_power = Some(10.0);
+    assert_eq!(plant_data.underperforming(0.5), None);
+
+    plant_data.nominal_power = None;
+    assert_eq!(plant_data.underperforming(0.5), None);
+}
+
+#[test]
+fn test_device_event_from_value() {
+    let event = DeviceEvent::from_value(&serde_json::json!({
+        "time": "2025-04-26 08:15:00",
+        "operType": "Setting",
+        "content": "Set export limit to 60%"
+    }))
+    .unwrap();
+
+    assert_eq!(event.time, NaiveDate::from_ymd_opt(2025, 4, 26).unwrap().and_hms_opt(8, 15, 0).unwrap());
+    assert_eq!(event.event_type, "Setting");
+    assert_eq!(event.description, "Set export limit to 60%");
+
+    assert!(DeviceEvent::from_value(&serde_json::json!({ "content": "no time" })).is_none());
+}
+
+#[test]
+fn test_plant_data_current_power_in_kilowatts() {
+    let mut plant_data: PlantData = serde_json::from_str(r#"{
+        "currentPower": 4.5,
+        "currentPowerStr": "4.5kW"
+    }"#).unwrap();
+
+    assert_eq!(plant_data.current_power_watts(), Some(4500.0));
+    plant_data.normalize_units(&AccountUnits::default());
+    assert_eq!(plant_data.current_power, Some(4500.0));
+}
+
+#[test]
+fn test_plant_data_current_power_in_watts() {
+    let plant_data: PlantData = serde_json::from_str(r#"{
+        "currentPower": 4500.0,
+        "currentPowerStr": "4500 W"
+    }"#).unwrap();
+    assert_eq!(plant_data.current_power_watts(), Some(4500.0));
+
+    // Without a unit the value is taken as watts
+    let plant_data: PlantData = serde_json::from_str(r#"{ "currentPower": 850.0 }"#).unwrap();
+    assert_eq!(plant_data.current_power_watts(), Some(850.0));
+}
+
+#[test]
+fn test_mix_device_list() {
+    let json_data = r#"[
+        { "deviceSn": "MIX0000001", "alias": "Garage", "lost": false, "status": "1", "datalogSn": "DL00000001" },
+        { "deviceSn": "MIX0000002", "lost": "true", "status": -1 }
+    ]"#;
+
+    let devices: Vec<MixDevice> = serde_json::from_str(json_data).unwrap();
+
+    assert_eq!(devices[0].device_sn, "MIX0000001");
+    assert_eq!(devices[0].alias.as_deref(), Some("Garage"));
+    assert_eq!(devices[0].status, Some(1));
+    assert_eq!(devices[0].datalog_sn.as_deref(), Some("DL00000001"));
+    assert!(devices[0].is_online());
+
+    assert_eq!(devices[1].lost, Some(true));
+    assert_eq!(devices[1].status, Some(-1));
+    assert!(!devices[1].is_online());
+}
+
+#[test]
+fn test_poll_schedule_jitter() {
+    let interval = std::time::Duration::from_secs(60);
+    let window = std::time::Duration::from_secs(10);
+
+    let plain = PollSchedule::from(interval);
+    assert_eq!(plain.first_delay(), std::time::Duration::ZERO);
+    assert_eq!(plain.next_delay(), interval);
+
+    let jittered = PollSchedule::every(interval).with_jitter(window);
+    for _ in 0..100 {
+        assert!(jittered.first_delay() < window);
+        let delay = jittered.next_delay();
+        assert!(delay >= interval && delay < interval + window);
+    }
+}
+
+#[test]
+fn test_device_struct() {
+    let device: Device = serde_json::from_str(r#"{
+        "deviceSn": "INV0000001",
+        "deviceType": "max",
+        "alias": "Roof East",
+        "status": "1",
+        "lost": false,
+        "lastUpdateTime": "2025-04-26 11:55:00",
+        "datalogSn": "DL00000001",
+        "pac": "15200.5"
+    }"#).unwrap();
+
+    assert_eq!(device.device_sn, "INV0000001");
+    assert_eq!(device.device_type.as_deref(), Some("max"));
+    assert_eq!(device.status, Some(1));
+    assert_eq!(
+        device.last_update_time,
+        Some(NaiveDate::from_ymd_opt(2025, 4, 26).unwrap().and_hms_opt(11, 55, 0).unwrap())
+    );
+    assert_eq!(device.datalogger_sn.as_deref(), Some("DL00000001"));
+    assert_eq!(device.power, Some(15200.5));
+}
+
+#[test]
+fn test_login_result_role() {
+    let login = LoginResult::from_value(&serde_json::json!({
+        "result": 1,
+        "user": { "id": 123456, "accountType": "1" },
+        "token": "abc"
+    }));
+    assert_eq!(login.role, Some(Role::Installer));
+    assert_eq!(login.user_id.as_deref(), Some("123456"));
+    assert_eq!(login.token.as_deref(), Some("abc"));
+
+    // Older servers put userType at the top level
+    let login = LoginResult::from_value(&serde_json::json!({ "result": 1, "userType": 0 }));
+    assert_eq!(login.role, Some(Role::EndUser));
+
+    let login = LoginResult::from_value(&serde_json::json!({ "result": 1, "accountType": 9 }));
+    assert_eq!(login.role, Some(Role::Unknown(9)));
+
+    let login = LoginResult::from_value(&serde_json::json!({ "result": 1 }));
+    assert_eq!(login.role, None);
+}
+
+#[tokio::test]
+async fn test_response_size_limit() {
+    let mut server = mockito::Server::new_async().await;
+    let body = format!(r#"{{"result": 1, "obj": {{"plantName": "{}"}}}}"#, "x".repeat(2048));
+    let _mock = json_mock(&mut server, "POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url()).with_max_response_size(1024);
+    assert!(matches!(
+        client.get_plant("1").await,
+        Err(GrowattError::ResponseTooLarge { limit: 1024 })
+    ));
+
+    let mut client = logged_in_client(&server.url());
+    assert!(client.get_plant("1").await.is_ok());
+}
+
+#[test]
+fn test_meter_phases() {
+    let three_phase = MeterPhases::from_value(&serde_json::json!({
+        "activePowerA": "1200.5", "voltageA": "230.1", "currentA": "5.2",
+        "activePowerB": 800, "voltageB": 229.8, "currentB": 3.5,
+        "activePowerC": -300, "voltageC": 231.0, "currentC": 1.3
+    }));
+    assert!(three_phase.is_three_phase());
+    assert_eq!(three_phase.a.active_power, Some(1200.5));
+    assert_eq!(three_phase.c.unwrap().active_power, Some(-300.0));
+
+    // Single-phase meters report zeros for B and C, using the R/S/T naming
+    let single_phase = MeterPhases::from_value(&serde_json::json!({
+        "pacR": 950, "vacR": 230, "iacR": 4.1,
+        "pacS": 0, "vacS": 0, "iacS": 0
+    }));
+    assert!(!single_phase.is_three_phase());
+    assert_eq!(single_phase.a.voltage, Some(230.0));
+    assert!(single_phase.b.is_none());
+    assert!(single_phase.c.is_none());
+}
+
+#[tokio::test]
+async fn test_login_and_list() {
+    let mut server = mockito::Server::new_async().await;
+    let _login = server
+        .mock("POST", "/login")
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .create_async()
+        .await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new();
+    client.base_url = server.url();
+    let plants = client.login_and_list("user", "pass").await.unwrap();
+    assert_eq!(plants.0.len(), 1);
+    assert_eq!(plants.0[0].plant_name, "Roof");
+}
+
+#[tokio::test]
+async fn test_login_and_list_rejected_login() {
+    let mut server = mockito::Server::new_async().await;
+    let _login = server
+        .mock("POST", "/login")
+        .with_body(r#"{"result": 0, "msg": "bad password"}"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new();
+    client.base_url = server.url();
+    assert!(matches!(
+        client.login_and_list("user", "wrong").await,
+        Err(GrowattError::AuthError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_credential_failover() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _primary = server
+        .mock("POST", "/login")
+        .match_body(Matcher::UrlEncoded("account".into(), "primary".into()))
+        .with_body(r#"{"result": 0, "msg": "account locked"}"#)
+        .create_async()
+        .await;
+    let _backup = server
+        .mock("POST", "/login")
+        .match_body(Matcher::UrlEncoded("account".into(), "backup".into()))
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new().with_credentials(vec![
+        Credentials::new("primary", "one"),
+        Credentials::new("backup", "two"),
+    ]);
+    client.base_url = server.url();
+    assert_eq!(client.active_username(), Some("primary"));
+
+    client.login_with_failover().await.unwrap();
+    assert!(client.is_logged_in);
+    assert_eq!(client.active_username(), Some("backup"));
+}
+
+#[tokio::test]
+async fn test_credential_failover_on_captcha() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _primary = server
+        .mock("POST", "/login")
+        .match_body(Matcher::UrlEncoded("account".into(), "primary".into()))
+        .with_body(r#"{"result": 0, "msg": "Please enter the verification code", "needValidateCode": true}"#)
+        .create_async()
+        .await;
+    let _backup = server
+        .mock("POST", "/login")
+        .match_body(Matcher::UrlEncoded("account".into(), "backup".into()))
+        .with_body(r#"{"result": 1, "user": {"id": 7}}"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new().with_credentials(vec![
+        Credentials::new("primary", "one"),
+        Credentials::new("backup", "two"),
+    ]);
+    client.base_url = server.url();
+
+    client.login_with_failover().await.unwrap();
+    assert!(client.is_logged_in);
+    assert_eq!(client.active_username(), Some("backup"));
+}
+
+#[test]
+fn test_set_command_time_sync() {
+    use chrono::{FixedOffset, TimeZone};
+
+    // The wall-clock time in the given zone is sent, not UTC
+    let bangkok = FixedOffset::east_opt(7 * 3600).unwrap();
+    let time = bangkok.with_ymd_and_hms(2025, 4, 26, 9, 5, 0).unwrap();
+    let command = SetCommand::time_sync(time.naive_local());
+    assert_eq!(command.action(), "mixSet");
+    assert_eq!(command.param_type(), "pf_sys_year");
+    assert_eq!(command.params(), ["2025-04-26 09:05:00"]);
+}
+
+#[test]
+fn test_plant_snapshot_diff() {
+    let previous = PlantSnapshot {
+        taken_at: Utc::now(),
+        online: true,
+        current_power: Some(4000.0),
+        battery: Some(BatteryState::Idle),
+        faults: vec![FaultCode("101".to_string())],
+    };
+
+    // Small fluctuations are not reported
+    let mut current = previous.clone();
+    current.current_power = Some(4100.0);
+    assert!(current.diff(&previous).is_empty());
+
+    let current = PlantSnapshot {
+        taken_at: Utc::now(),
+        online: false,
+        current_power: Some(0.0),
+        battery: Some(BatteryState::Charging),
+        faults: vec![FaultCode("102".to_string())],
+    };
+    assert_eq!(
+        current.diff(&previous),
+        vec![
+            Change::WentOffline,
+            Change::PowerChanged { from: Some(4000.0), to: Some(0.0) },
+            Change::BatteryStateChanged { from: Some(BatteryState::Idle), to: Some(BatteryState::Charging) },
+            Change::FaultAppeared(FaultCode("102".to_string())),
+            Change::FaultCleared(FaultCode("101".to_string())),
+        ]
+    );
+
+    assert_eq!(previous.diff(&current)[0], Change::CameOnline);
+}
+
+#[test]
+fn test_string_data_ignores_padding() {
+    // A 2-MPPT inverter whose response pads out to 4 trackers
+    let obj = serde_json::json!({
+        "vpv1": "380.2", "ipv1": "5.1", "ppv1": "1939",
+        "vpv2": 0, "ipv2": 0, "ppv2": 0,
+        "vpv3": 0, "ipv3": 0, "ppv3": 0,
+        "vpv4": 0, "ipv4": 0, "ppv4": 0
+    });
+
+    // With the tracker count, a dead second string is still reported
+    let mppts = parse_mppts(&obj, Some(2));
+    assert_eq!(mppts.len(), 2);
+    assert_eq!(mppts[0].voltage, Some(380.2));
+    assert_eq!(mppts[1].index, 2);
+    assert_eq!(mppts[1].power, Some(0.0));
+
+    // Without it, trailing zero trackers are treated as padding
+    assert_eq!(parse_mppts(&obj, None).len(), 1);
+
+    // A bogus tracker count doesn't invent trackers past the field limit
+    assert_eq!(parse_mppts(&obj, Some(255)).len(), 16);
+
+    let info = DeviceInfo::from_value("INV1", &serde_json::json!({"modelText": "MIN 5000TL-X", "mpptNum": "2"}));
+    assert_eq!(info.mppt_count, Some(2));
+    assert_eq!(info.model.as_deref(), Some("MIN 5000TL-X"));
+}
+
+#[tokio::test]
+async fn test_get_device_info_nominal_power_in_watts() {
+    let mut server = mockito::Server::new_async().await;
+    let _info = json_mock(&mut server, "POST", "/panel/getDeviceInfo")
+        .with_body(r#"{"result": 1, "obj": {"model": "MIN 5000TL-X", "nominalPower": "6.6"}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.account_units = Some(AccountUnits {
+        power: PowerUnit::Kilowatt,
+        ..AccountUnits::default()
+    });
+    let info = client.get_device_info("INV1").await.unwrap();
+    assert_eq!(info.nominal_power, Some(6600.0));
+}
+
+#[tokio::test]
+async fn test_openapi_v1_endpoints() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "GET", "/v1/plant/list")
+        .match_header("token", "secret")
+        .with_body(r#"{"error_code": 0, "error_msg": "", "data": {"count": 1, "plants": [
+            {"plant_id": 42, "name": "Roof", "city": "Bangkok", "current_power": 1500}
+        ]}}"#)
+        .create_async()
+        .await;
+    let _plant = json_mock(&mut server, "GET", "/v1/plant/data")
+        .match_header("token", "secret")
+        .match_query(mockito::Matcher::UrlEncoded("plant_id".into(), "42".into()))
+        .with_body(r#"{"error_code": 0, "data": {"current_power": 1500, "today_energy": "7.2", "total_energy": "1234.5"}}"#)
+        .create_async()
+        .await;
+
+    assert_eq!(Growatt::new().api_mode(), crate::ApiMode::WebPortal);
+    let mut client = Growatt::new().with_api_token("secret");
+    client.base_url = server.url();
+    assert!(client.uses_openapi());
+    assert_eq!(client.api_mode(), crate::ApiMode::OpenApiV1);
+
+    let plants = client.get_plants().await.unwrap();
+    assert_eq!(plants.0[0].plant_id, "42");
+    assert_eq!(plants.0[0].plant_name, "Roof");
+
+    let plant = client.get_plant("42").await.unwrap();
+    assert_eq!(plant.today_energy, Some(7.2));
+    assert_eq!(plant.current_power, Some(1500.0));
+    assert!(!client.is_logged_in);
+}
+
+#[test]
+fn test_api_token_routes_to_openapi_host() {
+    let client = Growatt::new().with_api_token("secret");
+    let request = client.openapi_request("/v1/plant/list", &[]).unwrap().build().unwrap();
+    assert_eq!(request.url().as_str(), "https://openapi.growatt.com/v1/plant/list");
+    assert_eq!(request.headers()["token"], "secret");
+
+    // A base URL set by the caller, e.g. a gateway, is kept
+    let client = Growatt::new().with_base_url("https://gw.corp/growatt").with_api_token("secret");
+    let request = client.openapi_request("/v1/plant/list", &[]).unwrap().build().unwrap();
+    assert_eq!(request.url().as_str(), "https://gw.corp/growatt/v1/plant/list");
+}
+
+#[tokio::test]
+async fn test_openapi_v1_invalid_token() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "GET", "/v1/plant/list")
+        .with_body(r#"{"error_code": 10011, "error_msg": "error_permission_denied"}"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new().with_api_token("wrong");
+    client.base_url = server.url();
+    assert!(matches!(client.get_plants().await, Err(GrowattError::AuthError(_))));
+}
+
+#[tokio::test]
+async fn test_request_metrics_hook() {
+    use std::sync::{Arc, Mutex};
+
+    let mut server = mockito::Server::new_async().await;
+    let _ok = json_mock(&mut server, "POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"obj": {"plantName": "Roof"}}"#)
+        .create_async()
+        .await;
+    let _failed = server
+        .mock("POST", "/panel/getDevicesByPlant")
+        .match_query(mockito::Matcher::Any)
+        .with_status(500)
+        .create_async()
+        .await;
+    let _login_page = server
+        .mock("POST", "/device/getMAXList")
+        .with_header("content-type", "text/html")
+        .with_body("<html></html>")
+        .create_async()
+        .await;
+    let _garbled = json_mock(&mut server, "POST", "/device/getEnvList")
+        .with_body(r#"{"result": 1, "obj": "#)
+        .create_async()
+        .await;
+
+    let recorded: Arc<Mutex<Vec<RequestMetrics>>> = Arc::default();
+    let sink = Arc::clone(&recorded);
+    let mut client = logged_in_client(&server.url())
+        .with_circuit_breaker(4, std::time::Duration::from_secs(60))
+        .on_request_complete(move |metrics| sink.lock().unwrap().push(metrics));
+
+    client.get_plant("1").await.unwrap();
+    assert!(client.get_mix_ids("1").await.is_err());
+    assert!(client.get_device_list("1").await.is_err());
+    // The login page marked the session as gone
+    client.is_logged_in = true;
+    assert!(client.get_weather("1").await.is_err());
+
+    // The breaker only opens on server errors, so trip it by hand
+    for _ in 0..4 {
+        client.circuit_breaker().unwrap().record_failure();
+    }
+    assert!(matches!(client.get_plant_fresh("1").await, Err(GrowattError::CircuitOpen)));
+
+    let outcomes: Vec<_> = recorded
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|metrics| (metrics.endpoint.clone(), metrics.outcome))
+        .collect();
+    assert_eq!(outcomes, [
+        ("/panel/getPlantData".to_string(), Ok(())),
+        ("/panel/getDevicesByPlant".to_string(), Err(ErrorKind::Request)),
+        ("/device/getMAXList".to_string(), Err(ErrorKind::SessionExpired)),
+        ("/device/getEnvList".to_string(), Err(ErrorKind::Json)),
+        ("/panel/getPlantData".to_string(), Err(ErrorKind::CircuitOpen)),
+    ]);
+    assert_eq!(recorded.lock().unwrap()[1].retries, 0);
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_plant_data_cache_stale_while_revalidate() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = json_mock(&mut server, "POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"obj": {"plantName": "Roof", "currentPower": 1500}}"#)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let ttl = std::time::Duration::from_millis(200);
+    let grace = std::time::Duration::from_millis(400);
+    let mut client = logged_in_client(&server.url()).with_plant_data_cache(ttl, grace);
+
+    // Miss, then fresh hit
+    client.get_plant("1").await.unwrap();
+    client.get_plant("1").await.unwrap();
+
+    // Stale: served immediately, refreshed once in the background
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    let stale = client.get_plant("1").await.unwrap();
+    assert_eq!(stale.current_power, Some(1500.0));
+    client.get_plant("1").await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // Past the grace period: fetched again before returning
+    tokio::time::sleep(std::time::Duration::from_millis(700)).await;
+    client.get_plant("1").await.unwrap();
+
+    mock.assert_async().await;
+}
+
+#[test]
+fn test_plant_address() {
+    let address = Address::from_value(&serde_json::json!({
+        "country": "Thailand",
+        "city": "Bangkok",
+        "plantAddress": "99 Sukhumvit Rd, Khlong Toei, Bangkok"
+    }));
+    assert_eq!(address.country.as_deref(), Some("Thailand"));
+    assert_eq!(address.city.as_deref(), Some("Bangkok"));
+    assert_eq!(address.street.as_deref(), Some("99 Sukhumvit Rd"));
+    assert_eq!(address.raw.as_deref(), Some("99 Sukhumvit Rd, Khlong Toei, Bangkok"));
+
+    let address = Address::from_value(&serde_json::json!({"country": "", "plantAddress": ""}));
+    assert_eq!(address, Address::default());
+}
+
+#[test]
+fn test_panel_config() {
+    let config = PanelConfig::from_value(&serde_json::json!({"panelNum": "24", "panelPower": 410}));
+    assert_eq!(config.panel_count, Some(24));
+    assert_eq!(config.panel_watts, Some(410));
+    assert_eq!(config.total_dc_capacity(), Some(9840));
+
+    let config = PanelConfig::from_value(&serde_json::json!({"panelNum": 0, "panelPower": ""}));
+    assert_eq!(config, PanelConfig::default());
+    assert_eq!(config.total_dc_capacity(), None);
+}
+
+#[tokio::test]
+async fn test_test_logged_in_skips_login() {
+    let mut server = mockito::Server::new_async().await;
+    let login = server.mock("POST", "/login").expect(0).create_async().await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::test_logged_in(&server.url());
+    assert!(client.is_logged_in());
+    assert_eq!(client.get_plants().await.unwrap().0.len(), 1);
+    login.assert_async().await;
+}
+
+#[test]
+fn test_account_units_conversion() {
+    let units = AccountUnits::from_value(&serde_json::json!({
+        "result": 1,
+        "user": {"powerUnit": "kW", "energyUnit": "MWh", "temperatureUnit": "℉"}
+    }));
+    assert_eq!(units.power, PowerUnit::Kilowatt);
+    assert_eq!(units.energy, EnergyUnit::MegawattHour);
+    assert_eq!(units.temperature, TemperatureUnit::Fahrenheit);
+    assert_eq!(units.to_celsius(212.0), 100.0);
+
+    let mut plant_data: PlantData = serde_json::from_value(serde_json::json!({
+        "currentPower": 4.5,
+        "ratedPower": 5,
+        "todayEnergy": 0.02,
+        "totalEnergy": 1.5
+    }))
+    .unwrap();
+    plant_data.normalize_units(&units);
+    plant_data.to_si(&units);
+    assert_eq!(plant_data.current_power, Some(4500.0));
+    assert_eq!(plant_data.rated_power, Some(5000.0));
+    assert_eq!(plant_data.today_energy, Some(20_000.0));
+    assert_eq!(plant_data.total_energy, Some(1_500_000.0));
+
+    // A power that came with a unit suffix is already in watts
+    let mut plant_data: PlantData = serde_json::from_value(serde_json::json!({
+        "currentPower": 4.5,
+        "currentPowerStr": "4.5kW"
+    }))
+    .unwrap();
+    plant_data.normalize_units(&units);
+    plant_data.to_si(&units);
+    assert_eq!(plant_data.current_power, Some(4500.0));
+
+    // Missing settings fall back to the portal defaults
+    assert_eq!(AccountUnits::from_value(&serde_json::json!({"result": 1})), AccountUnits::default());
+}
+
+#[tokio::test]
+async fn test_clipping_status_in_kw_account() {
+    let mut server = mockito::Server::new_async().await;
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"currentPower": 3, "currentPowerStr": "3kW", "ratedPower": 5}}"#)
+        .create_async()
+        .await;
+
+    // The suffixed current power is read in W, the rated power in the account's kW
+    let mut client = logged_in_client(&server.url());
+    client.account_units = Some(AccountUnits {
+        power: PowerUnit::Kilowatt,
+        ..AccountUnits::default()
+    });
+    let plant_data = client.get_plant("1").await.unwrap();
+    assert_eq!(plant_data.current_power, Some(3000.0));
+    assert_eq!(plant_data.rated_power, Some(5000.0));
+    assert_eq!(plant_data.is_clipping(), Some(false));
+    assert_eq!(client.get_clipping_status("1").await.unwrap(), Some(false));
+
+    let mut client = logged_in_client(&server.url()).with_si_plant_units();
+    client.account_units = Some(AccountUnits {
+        power: PowerUnit::Kilowatt,
+        ..AccountUnits::default()
+    });
+    assert_eq!(client.get_plant("1").await.unwrap().rated_power, Some(5000.0));
+    assert_eq!(client.get_clipping_status("1").await.unwrap(), Some(false));
+}
+
+#[tokio::test]
+async fn test_clipping_status_without_rated_power() {
+    let mut server = mockito::Server::new_async().await;
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"currentPower": 5000, "nominalPower": 5000}}"#)
+        .create_async()
+        .await;
+    let devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .expect(0)
+        .create_async()
+        .await;
+
+    // The DC nameplate is no stand-in for the AC limit
+    let mut client = logged_in_client(&server.url());
+    assert_eq!(client.get_clipping_status("1").await.unwrap(), None);
+    devices.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_daily_energy_all_devices() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .with_body(r#"{"obj": {"pages": 1, "datas": [
+            {"deviceSn": "INV1", "deviceType": "inv"},
+            {"deviceSn": "MIX1", "deviceType": "mix"},
+            {"deviceSn": "INV2"},
+            {"deviceSn": "EV1", "deviceType": "charger"}
+        ]}}"#)
+        .create_async()
+        .await;
+    // Only the first page is read: it holds the day's latest running total
+    let inv1 = json_mock(&mut server, "POST", "/device/getInverterHistory")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("inverterSn".into(), "INV1".into()),
+            Matcher::UrlEncoded("startDate".into(), "2025-04-26".into()),
+            Matcher::UrlEncoded("endDate".into(), "2025-04-26".into()),
+        ]))
+        .with_body(r#"{"obj": {"haveNext": true, "start": 2, "datas": [
+            {"time": "2025-04-26 18:00:00", "eacToday": "21.3"},
+            {"time": "2025-04-26 12:00:00", "eacToday": "10.5"}
+        ]}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _mix1 = json_mock(&mut server, "POST", "/device/getMIXHistory")
+        .match_body(Matcher::UrlEncoded("mixSn".into(), "MIX1".into()))
+        .with_body(r#"{"obj": {"haveNext": false, "datas": [{"time": "2025-04-26 18:00:00", "epvToday": 8}]}}"#)
+        .create_async()
+        .await;
+    let _inv2 = server
+        .mock("POST", "/device/getInverterHistory")
+        .match_body(Matcher::UrlEncoded("inverterSn".into(), "INV2".into()))
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let date = NaiveDate::from_ymd_opt(2025, 4, 26).unwrap();
+    let breakdown = client.get_daily_energy_all_devices("1", date).await.unwrap();
+
+    assert_eq!(breakdown.energy, vec![("INV1".to_string(), 21.3), ("MIX1".to_string(), 8.0)]);
+    assert!(breakdown.is_partial());
+    assert_eq!(breakdown.failed.len(), 2);
+    assert_eq!(breakdown.failed[0].0, "INV2");
+    assert_eq!(breakdown.failed[1].0, "EV1");
+    assert!(matches!(breakdown.failed[1].1, GrowattError::InvalidInput(_)));
+    inv1.assert_async().await;
+}
+
+#[test]
+fn test_set_command_schedules() {
+    let period = TimePeriod {
+        start: NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(5, 0, 0).unwrap(),
+        enabled: true,
+    };
+
+    let command = SetCommand::battery_first_schedule(100, 95, true, std::slice::from_ref(&period)).unwrap();
+    assert_eq!(command.param_type(), "mix_ac_charge_time_period");
+    assert_eq!(
+        command.params(),
+        ["100", "95", "1", "1", "30", "5", "0", "1", "0", "0", "0", "0", "0", "0", "0", "0", "0", "0"]
+    );
+
+    // Extra periods are dropped
+    let periods = [period.clone(), period.clone(), period.clone(), period.clone()];
+    let command = SetCommand::grid_first_schedule(100, 10, &periods).unwrap();
+    assert_eq!(command.param_type(), "mix_ac_discharge_time_period");
+    assert_eq!(command.params().len(), 2 + 3 * 5);
+    assert_eq!(command.params()[0], "100");
+
+    // Out of range rates and SOCs are rejected, not capped
+    assert!(matches!(SetCommand::grid_first_schedule(150, 10, &[]), Err(GrowattError::InvalidInput(_))));
+    assert!(matches!(
+        SetCommand::battery_first_schedule(100, 250, true, std::slice::from_ref(&period)),
+        Err(GrowattError::InvalidInput(_))
+    ));
+    assert!(matches!(SetCommand::ac_charge_schedule(50, 101, &[]), Err(GrowattError::InvalidInput(_))));
+
+    let command = SetCommand::ac_charge_schedule(50, 90, &[]).unwrap();
+    assert_eq!(command.action(), "spaSet");
+    assert_eq!(command.params().len(), 2 + 3 * 5);
+
+    assert_eq!(SetCommand::on_off(false).params(), ["0000"]);
+}
+
+#[tokio::test]
+async fn test_connection_options_keep_working_client() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url())
+        .with_tcp_keepalive(std::time::Duration::from_secs(60))
+        .with_http2_keep_alive_interval(std::time::Duration::from_secs(30));
+    assert_eq!(client.get_plants().await.unwrap().0.len(), 1);
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_get_plant_fresh_bypasses_cache() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = json_mock(&mut server, "POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"obj": {"plantName": "Roof"}}"#)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let minute = std::time::Duration::from_secs(60);
+    let mut client = logged_in_client(&server.url()).with_plant_data_cache(minute, minute);
+
+    client.get_plant("1").await.unwrap();
+    client.get_plant_fresh("1").await.unwrap();
+    // Served from the entry the fresh fetch stored
+    client.get_plant("1").await.unwrap();
+
+    mock.assert_async().await;
+}
+
+#[test]
+fn test_battery_cells() {
+    let cells = BatteryCells::from_value(&serde_json::json!({
+        "cellVoltage1": "3312", "cellTemp1": "24.5",
+        "cellVoltage2": 3298, "cellTemp2": 25.0,
+        "cellVoltage3": 3320, "cellTemp3": "26.1"
+    }))
+    .unwrap();
+    assert_eq!(cells.cells.len(), 3);
+    assert_eq!(cells.min_voltage_mv, Some(3298.0));
+    assert_eq!(cells.max_voltage_mv, Some(3320.0));
+    assert_eq!(cells.delta_mv, Some(22.0));
+    assert_eq!(cells.max_temp_c, Some(26.1));
+
+    // Batteries without cell reporting
+    assert!(BatteryCells::from_value(&serde_json::json!({"soc": 80})).is_none());
+}
+
+#[tokio::test]
+async fn test_forbidden_plant() {
+    let mut server = mockito::Server::new_async().await;
+    // The result code decides, whatever language the message is in
+    let _coded = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=997")
+        .with_body(r#"{"result": 10011, "msg": "ไม่มีสิทธิ์เข้าถึง", "obj": {}}"#)
+        .create_async()
+        .await;
+    // Without the code, an English message is the fallback
+    let _english = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=999")
+        .with_body(r#"{"result": 0, "msg": "No permission to view this plant", "obj": {}}"#)
+        .create_async()
+        .await;
+    // A localized message without the code can't be told apart from other
+    // failures
+    let _localized = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=998")
+        .with_body(r#"{"result": 0, "msg": "无权限", "obj": {}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    for id in ["997", "999"] {
+        match client.get_plant(id).await {
+            Err(GrowattError::Forbidden { plant_id }) => assert_eq!(plant_id, id),
+            other => panic!("expected Forbidden, got {:?}", other),
+        }
+    }
+    assert!(matches!(client.get_plant("998").await, Err(GrowattError::InvalidResponse(_))));
+
+    // Plant-scoped calls outside getPlantData are checked too
+    let _alarms = json_mock(&mut server, "POST", "/alarm/getPlantAlarmSetList")
+        .with_body(r#"{"result": 0, "msg": "No permission to view this plant", "obj": null}"#)
+        .create_async()
+        .await;
+    assert!(matches!(client.get_plant_alarms("999").await, Err(GrowattError::Forbidden { .. })));
+}
+
+// The response the portal gives for a plant that belongs to another account
+const NO_PERMISSION_BODY: &str = r#"{"result": 10011, "msg": "No permission", "obj": null}"#;
+
+#[tokio::test]
+async fn test_meter_phases_forbidden() {
+    let mut server = mockito::Server::new_async().await;
+    let _meter = json_mock(&mut server, "POST", "/panel/meter/getMeterStatusData?plantId=999")
+        .with_body(NO_PERMISSION_BODY)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(matches!(client.get_meter_phases("999", "METER1").await, Err(GrowattError::Forbidden { .. })));
+}
+
+#[tokio::test]
+async fn test_plant_weather_forbidden() {
+    let mut server = mockito::Server::new_async().await;
+    let _weather = json_mock(&mut server, "POST", "/index/getWeatherByPlantId")
+        .with_body(NO_PERMISSION_BODY)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(matches!(client.get_plant_weather("999").await, Err(GrowattError::Forbidden { .. })));
+}
+
+#[tokio::test]
+async fn test_energy_chart_forbidden() {
+    let mut server = mockito::Server::new_async().await;
+    let _day = json_mock(&mut server, "POST", "/panel/mix/getMIXEnergyDayChart")
+        .with_body(NO_PERMISSION_BODY)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let day = NaiveDate::from_ymd_opt(2025, 4, 26).unwrap();
+    assert!(matches!(
+        client.get_energy_stats_daily_typed(ChartDay(day), "999", "MIX1").await,
+        Err(GrowattError::Forbidden { .. })
+    ));
+    assert!(matches!(
+        client.get_energy_range_daily(day, day, "999", "MIX1").await,
+        Err(GrowattError::Forbidden { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_device_list_forbidden() {
+    let mut server = mockito::Server::new_async().await;
+    let _devices = json_mock(&mut server, "POST", "/device/getMAXList")
+        .with_body(NO_PERMISSION_BODY)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(matches!(client.get_device_list("999").await, Err(GrowattError::Forbidden { .. })));
+}
+
+#[test]
+fn test_alarm_rule_from_value() {
+    let rule = AlarmRule::from_value(&serde_json::json!({
+        "id": 17, "alarmType": "eToday", "compareType": "0", "threshold": "5.5", "enable": 1
+    }))
+    .unwrap();
+    assert_eq!(rule.id.as_deref(), Some("17"));
+    assert_eq!(rule.metric, "eToday");
+    assert_eq!(rule.comparison, Comparison::Below);
+    assert_eq!(rule.threshold, 5.5);
+    assert!(rule.enabled);
+
+    // Unknown comparison codes are skipped rather than guessed
+    assert!(AlarmRule::from_value(&serde_json::json!({
+        "alarmType": "pac", "compareType": 7, "threshold": 100
+    }))
+    .is_none());
+}
+
+#[tokio::test]
+async fn test_reads_after_shared_logout_fail() {
+    let mut server = mockito::Server::new_async().await;
+    let _logout = server.mock("GET", "/logout").with_status(302).create_async().await;
+    let plant = server
+        .mock("POST", "/panel/getPlantData")
+        .match_query(mockito::Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let mut shared = client.clone();
+
+    assert!(client.logout().await.unwrap());
+    assert!(matches!(shared.get_plant("1").await, Err(GrowattError::NotLoggedIn)));
+    plant.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_base_url_with_path_prefix() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "POST", "/growatt/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    for prefix in ["/growatt", "/growatt/"] {
+        let mut client = logged_in_client("unused").with_base_url(&format!("{}{}", server.url(), prefix));
+        assert_eq!(client.get_plants().await.unwrap().0.len(), 1);
+    }
+}
+
+#[test]
+fn test_gap_fill() {
+    let day = |d| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+    let series = [(day(2), 10.0), (day(5), 16.0)];
+    let values = |filled: Vec<(NaiveDate, f64)>| filled.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+
+    assert_eq!(GapFill::None.apply(&series, day(1), day(6)), series.to_vec());
+    assert_eq!(values(GapFill::Zero.apply(&series, day(1), day(6))), [0.0, 10.0, 0.0, 0.0, 16.0, 0.0]);
+    assert_eq!(values(GapFill::Interpolate.apply(&series, day(1), day(6))), [10.0, 10.0, 12.0, 14.0, 16.0, 16.0]);
+
+    // Nothing to carry into the first day
+    let carried = GapFill::CarryForward.apply(&series, day(1), day(6));
+    assert_eq!(carried[0].0, day(2));
+    assert_eq!(values(carried), [10.0, 10.0, 10.0, 16.0, 16.0]);
+}
+
+#[tokio::test]
+async fn test_daily_pv_energy_range_inverted() {
+    let day = |d| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+    let mut client = logged_in_client("unused");
+    assert!(matches!(
+        client.get_daily_pv_energy_range("1", "MIX1", day(5), day(2), GapFill::Zero).await,
+        Err(GrowattError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_month_chart_series() {
+    let month = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    let chart = serde_json::json!({"obj": {"charts": {"ppv": ["12.5", null, 8]}}});
+    assert_eq!(
+        month_chart_series(&chart, month),
+        [(month, 12.5), (NaiveDate::from_ymd_opt(2025, 2, 3).unwrap(), 8.0)]
+    );
+}
+
+#[test]
+fn test_day_power_curve() {
+    let pac: Vec<serde_json::Value> = (0..288).map(|i| if i == 1 { serde_json::Value::Null } else { (i * 10).into() }).collect();
+    let chart = serde_json::json!({"obj": {"charts": {"pac": pac, "ppv": ["100", "110"]}}});
+
+    let curve = day_power_curve(&chart);
+    assert_eq!(curve.len(), 288);
+    assert_eq!(curve[1].time, chrono::NaiveTime::from_hms_opt(0, 5, 0).unwrap());
+    assert_eq!(curve[1].pac, None);
+    assert_eq!(curve[1].ppv, Some(110.0));
+    assert_eq!(curve[287].time, chrono::NaiveTime::from_hms_opt(23, 55, 0).unwrap());
+    assert_eq!(curve[287].pac, Some(2870.0));
+    assert_eq!(curve[287].ppv, None);
+
+    let empty = serde_json::json!({"obj": {"charts": {"pac": []}}});
+    assert!(day_power_curve(&empty).is_empty());
+
+    // More points than seconds in a day still step forward in time
+    let dense = serde_json::json!({"obj": {"charts": {"pac": vec![1; 90_000]}}});
+    let curve = day_power_curve(&dense);
+    assert_eq!(curve.len(), 24 * 60 * 60);
+    assert_eq!(curve[1].time, chrono::NaiveTime::from_hms_opt(0, 0, 1).unwrap());
+}
+
+#[test]
+fn test_resolve_device_state() {
+    assert_eq!(resolve_device_state(false, 1), DeviceState::Producing);
+    assert_eq!(resolve_device_state(true, 1), DeviceState::Offline);
+    assert_eq!(resolve_device_state(false, -1), DeviceState::Offline);
+    assert_eq!(resolve_device_state(false, 0), DeviceState::Standby);
+    assert_eq!(resolve_device_state(false, 3), DeviceState::Fault);
+    assert_eq!(resolve_device_state(false, 7), DeviceState::Unknown(7));
+
+    let device: Device = serde_json::from_value(serde_json::json!({"deviceSn": "INV1", "lost": "true", "status": "1"})).unwrap();
+    assert_eq!(device.state(), Some(DeviceState::Offline));
+    let device: MixDevice = serde_json::from_value(serde_json::json!({"deviceSn": "MIX1", "status": 3})).unwrap();
+    assert_eq!(device.state(), Some(DeviceState::Fault));
+    let device: Device = serde_json::from_value(serde_json::json!({"deviceSn": "INV2"})).unwrap();
+    assert_eq!(device.state(), None);
+}
+
+#[test]
+fn test_classify_devices() {
+    let devices = classify_devices(&serde_json::json!({
+        "mix": [{"deviceSn": "MIX1", "alias": "Garage"}],
+        "tlx": [["TLX1", "Roof", "1"], ["TLX2", "Shed", "0"]],
+        "pcs": [{"deviceSn": "PCS1"}],
+        "count": 4
+    }));
+
+    assert_eq!(devices.len(), 4);
+    assert_eq!(devices["MIX1"], DeviceType::Mix);
+    assert_eq!(devices["TLX2"], DeviceType::Tlx);
+    assert_eq!(devices["PCS1"], DeviceType::Other("pcs".to_string()));
+}
+
+#[tokio::test]
+async fn test_page_limit_stops_runaway_pagination() {
+    let mut server = mockito::Server::new_async().await;
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"plantName": "Roof"}}"#)
+        .create_async()
+        .await;
+    let devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .with_body(r#"{"obj": {"pages": 1000000, "datas": [{"deviceSn": "INV1"}]}}"#)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url()).with_max_pages(3);
+    match client.is_plant_online("1").await {
+        Err(GrowattError::PageLimitExceeded { limit }) => assert_eq!(limit, 3),
+        other => panic!("expected PageLimitExceeded, got {:?}", other),
+    }
+    devices.assert_async().await;
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_last_response_headers() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_header("x-cache", "HIT")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.get_plants().await.unwrap();
+    assert!(client.last_response_headers().is_none());
+
+    let mut client = logged_in_client(&server.url()).with_header_capture();
+    client.get_plants().await.unwrap();
+    let headers = client.last_response_headers().unwrap();
+    assert_eq!(headers.get("x-cache").unwrap(), "HIT");
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_response_inspector() {
+    use std::sync::{Arc, Mutex};
+
+    let mut server = mockito::Server::new_async().await;
+    let _total = json_mock(&mut server, "POST", "/panel/mix/getMIXTotalData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"epvToday": "12.3""#)
+        .create_async()
+        .await;
+
+    let seen: Arc<Mutex<Vec<(String, String)>>> = Arc::default();
+    let recorder = Arc::clone(&seen);
+    let mut client = logged_in_client(&server.url()).with_response_inspector(move |path, body| {
+        recorder.lock().unwrap().push((path.to_string(), body.to_string()));
+    });
+
+    // The body is seen even though parsing it fails
+    assert!(client.get_mix_total("1", "MIX1").await.is_err());
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].0, "/panel/mix/getMIXTotalData");
+    assert_eq!(seen[0].1, r#"{"result": 1, "obj": {"epvToday": "12.3""#);
+}
+
+#[tokio::test]
+async fn test_get_mix_total_typed() {
+    let mut server = mockito::Server::new_async().await;
+    let _total = json_mock(&mut server, "POST", "/panel/mix/getMIXTotalData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"epvToday": "12.3", "epv1Today": "7.1", "epv2Today": 5.2, "eChargeToday": "3", "elocalLoadToday": ""}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let total = client.get_mix_total("1", "MIX1").await.unwrap();
+    assert_eq!(total.epv_today, Some(12.3));
+    assert_eq!(total.epv1_today, Some(7.1));
+    assert_eq!(total.epv2_today, Some(5.2));
+    assert_eq!(total.e_charge_today, Some(3.0));
+    assert_eq!(total.e_discharge_today, None);
+    assert_eq!(total.elocal_load_today, None);
+}
+
+#[tokio::test]
+async fn test_get_mix_status_typed() {
+    let mut server = mockito::Server::new_async().await;
+    let _status = json_mock(&mut server, "POST", "/panel/mix/getMIXStatusData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"soc": "87", "vBat": 52.4, "pactogrid": "1.2", "priorityChoose": "1", "status": 3}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let status = client.get_mix_status_typed("1", "MIX1").await.unwrap();
+    assert_eq!(status.soc, Some(87.0));
+    assert_eq!(status.v_bat, Some(52.4));
+    assert_eq!(status.pactogrid, Some(1.2));
+    assert_eq!(status.priority_choose, Some(1));
+    assert_eq!(status.charge_power, None);
+    assert_eq!(status.run_state(), Some(MixRunState::Fault));
+
+    assert_eq!(MixStatusData::default().run_state(), None);
+    assert_eq!(MixRunState::from(9), MixRunState::Unknown(9));
+}
+
+#[tokio::test]
+async fn test_circuit_breaker() {
+    let mut server = mockito::Server::new_async().await;
+    let failing = server
+        .mock("POST", "/index/getPlantListTitle")
+        .with_status(503)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url()).with_circuit_breaker(2, std::time::Duration::from_millis(200));
+    assert!(client.get_plants().await.is_err());
+    assert!(client.get_plants().await.is_err());
+    assert!(client.circuit_breaker().unwrap().is_open());
+    assert!(matches!(client.get_plants().await, Err(GrowattError::CircuitOpen)));
+
+    // After the cooldown a trial request goes through, and its failure reopens
+    tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    assert!(matches!(client.get_plants().await, Err(GrowattError::RequestError(_))));
+    assert!(matches!(client.get_plants().await, Err(GrowattError::CircuitOpen)));
+    failing.assert_async().await;
+
+    server.reset();
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+    tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    assert!(client.get_plants().await.is_ok());
+    assert!(!client.circuit_breaker().unwrap().is_open());
+    assert!(client.get_plants().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_wait_until_online() {
+    let plant_body = r#"{"result": 1, "obj": {"plantName": "Roof"}}"#;
+    let mut server = mockito::Server::new_async().await;
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(plant_body)
+        .create_async()
+        .await;
+    let offline = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .with_body(r#"{"obj": {"pages": 1, "datas": [{"deviceSn": "INV1", "status": -1}, {"deviceSn": "INV2", "status": 1}]}}"#)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let started = tokio::time::Instant::now();
+    let online = client
+        .wait_until_online("1", "INV1", std::time::Duration::from_millis(250), std::time::Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(!online);
+    assert!(started.elapsed() >= std::time::Duration::from_millis(200));
+    offline.assert_async().await;
+
+    server.reset();
+    let _plant = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(plant_body)
+        .create_async()
+        .await;
+    let _online = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .with_body(r#"{"obj": {"pages": 1, "datas": [{"deviceSn": "INV1", "status": 1}]}}"#)
+        .create_async()
+        .await;
+    assert!(client
+        .wait_until_online("1", "inv1", std::time::Duration::from_millis(250), std::time::Duration::from_millis(100))
+        .await
+        .unwrap());
+}
+
+#[cfg(not(feature = "minimal"))]
+#[tokio::test]
+async fn test_portfolio_summary() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _night = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"currentPower": 0, "todayEnergy": "0.5"}}"#)
+        .create_async()
+        .await;
+    let _stale = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .match_body(Matcher::UrlEncoded("plantId".into(), "1".into()))
+        .with_body(r#"{"obj": {"pages": 1, "datas": [{"deviceSn": "INV1", "status": 1, "lastUpdateTime": "2020-01-01 00:00:00"}]}}"#)
+        .create_async()
+        .await;
+    let _day = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=2")
+        .with_body(r#"{"result": 1, "obj": {"currentPower": 1500, "todayEnergy": "7"}}"#)
+        .create_async()
+        .await;
+    let _reporting = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .match_body(Matcher::UrlEncoded("plantId".into(), "2".into()))
+        .with_body(r#"{"obj": {"pages": 1, "datas": [{"deviceSn": "INV2", "status": 1}]}}"#)
+        .create_async()
+        .await;
+
+    // A plant reporting zero power is offline if its devices stopped reporting
+    let mut client = logged_in_client(&server.url()).with_plant_concurrency(1);
+    let summary = client.get_portfolio_summary(&["1".to_string(), "2".to_string()]).await.unwrap();
+    assert_eq!(summary.online, 1);
+    assert_eq!(summary.offline, 1);
+    assert_eq!(summary.current_power, 1500.0);
+    assert_eq!(summary.today_energy, 7.5);
+    assert!(summary.errors.is_empty());
+}
+
+#[tokio::test]
+async fn test_retry_transient_failures() {
+    use std::sync::{Arc, Mutex};
+
+    let mut server = mockito::Server::new_async().await;
+    let failing = server
+        .mock("POST", "/index/getPlantListTitle")
+        .with_status(502)
+        .expect(2)
+        .create_async()
+        .await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let retries = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&retries);
+    let mut client = logged_in_client(&server.url())
+        .with_retry(3, std::time::Duration::from_millis(100))
+        .on_request_complete(move |metrics| recorded.lock().unwrap().push(metrics.retries));
+
+    let plants = client.get_plants().await.unwrap();
+    assert_eq!(plants.0.len(), 1);
+    failing.assert_async().await;
+    assert_eq!(*retries.lock().unwrap(), [2]);
+}
+
+#[tokio::test]
+async fn test_retry_skips_client_errors() {
+    let mut server = mockito::Server::new_async().await;
+    let forbidden = server
+        .mock("POST", "/index/getPlantListTitle")
+        .with_status(403)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url()).with_retry(3, std::time::Duration::from_millis(100));
+    assert!(client.get_plants().await.is_err());
+    forbidden.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .expect(10)
+        .create_async()
+        .await;
+
+    let client = logged_in_client(&server.url()).with_rate_limit(2.0);
+    let started = tokio::time::Instant::now();
+    let calls = (0..10).map(|_| {
+        let mut client = client.clone();
+        async move { client.get_plants().await }
+    });
+    for result in futures::future::join_all(calls).await {
+        assert!(result.is_ok());
+    }
+    assert!(started.elapsed() >= std::time::Duration::from_millis(4500));
+}
+
+#[test]
+fn test_rate_limit_minimum() {
+    // A rate too slow for a Duration is raised to one request a day
+    let client = Growatt::new().with_rate_limit(1e-300);
+    assert!(client.rate_limiter.is_some());
+}
+
+#[tokio::test]
+async fn test_group_energy() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _inv1 = json_mock(&mut server, "POST", "/device/getInverterHistory")
+        .match_body(Matcher::UrlEncoded("inverterSn".into(), "INV1".into()))
+        .with_body(r#"{"obj": {"haveNext": false, "datas": [{"time": "2025-04-26 18:00:00", "eacToday": "21.5"}]}}"#)
+        .create_async()
+        .await;
+    let _inv2 = json_mock(&mut server, "POST", "/device/getInverterHistory")
+        .match_body(Matcher::UrlEncoded("inverterSn".into(), "INV2".into()))
+        .with_body(r#"{"obj": {"haveNext": false, "datas": [{"time": "2025-04-26 18:00:00", "eacToday": 10}]}}"#)
+        .create_async()
+        .await;
+    let _inv3 = server
+        .mock("POST", "/device/getInverterHistory")
+        .match_body(Matcher::UrlEncoded("inverterSn".into(), "INV3".into()))
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let date = NaiveDate::from_ymd_opt(2025, 4, 26).unwrap();
+    let devices = ["INV1", "INV2", "INV3"].map(String::from);
+
+    let group = client.get_group_energy(&devices, date).await.unwrap();
+    assert_eq!(group.total_kwh, 31.5);
+    assert!(group.is_partial());
+    assert_eq!(group.failed.len(), 1);
+    assert_eq!(group.failed[0].0, "INV3");
+
+    assert!(client.get_group_energy(&devices[2..], date).await.is_err());
+}
+
+#[tokio::test]
+async fn test_per_call_timeout() {
+    let mut server = mockito::Server::new_async().await;
+    let _slow = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_chunked_body(|w| {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            w.write_all(br#"[{"id": "1", "plantName": "Roof"}]"#)
+        })
+        .create_async()
+        .await;
+
+    let client = logged_in_client(&server.url());
+    assert_eq!(client.timeout, crate::DEFAULT_TIMEOUT);
+
+    let quick = RequestOptions { timeout: Some(std::time::Duration::from_millis(50)) };
+    match client.with_options(quick).get_plants().await {
+        Err(GrowattError::RequestError(e)) => assert!(e.is_timeout()),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+
+    let mut client = client.with_timeout(std::time::Duration::from_secs(5));
+    assert!(client.get_plants().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_and_verify() {
+    let mut server = mockito::Server::new_async().await;
+    let _set = json_mock(&mut server, "POST", "/tcpSet.do")
+        .with_body(r#"{"success": true}"#)
+        .create_async()
+        .await;
+    let ignored = json_mock(&mut server, "POST", "/device/getMixSetParams")
+        .with_body(r#"{"obj": {"mixBean": {"activeRate": "100"}}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let settle = std::time::Duration::from_millis(10);
+    let command = SetCommand::active_power_limit(60).unwrap();
+    match client.set_and_verify("MIX1", &command, settle).await {
+        Err(GrowattError::NotApplied { param_type }) => assert_eq!(param_type, "pv_active_p_rate"),
+        other => panic!("expected NotApplied, got {:?}", other),
+    }
+
+    ignored.remove_async().await;
+    let _applied = json_mock(&mut server, "POST", "/device/getMixSetParams")
+        .with_body(r#"{"obj": {"mixBean": {"activeRate": "60"}}}"#)
+        .create_async()
+        .await;
+    assert!(client.set_and_verify("MIX1", &command, settle).await.unwrap());
+
+    let time_sync = SetCommand::time_sync(NaiveDate::from_ymd_opt(2025, 4, 26).unwrap().and_hms_opt(9, 0, 0).unwrap());
+    assert!(matches!(
+        client.set_and_verify("MIX1", &time_sync, settle).await,
+        Err(GrowattError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_schedule_applied_in_settings() {
+    let period = TimePeriod {
+        start: NaiveTime::from_hms_opt(1, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(5, 0, 0).unwrap(),
+        enabled: true,
+    };
+    let command = SetCommand::battery_first_schedule(100, 95, true, std::slice::from_ref(&period)).unwrap();
+    let settings = MixSettings {
+        charge_power_rate: Some(100.0),
+        charge_stop_soc: Some(95.0),
+        ac_charge_enabled: Some(true),
+        charge_periods: [Some(period.clone()), None, None],
+        ..MixSettings::default()
+    };
+    assert_eq!(command.applied_in(&settings), Some(true));
+
+    // The same period in another slot is a different setting
+    let moved = MixSettings { charge_periods: [None, Some(period), None], ..settings.clone() };
+    assert_eq!(command.applied_in(&moved), Some(false));
+
+    let stale = MixSettings { charge_periods: Default::default(), ..settings };
+    assert_eq!(command.applied_in(&stale), Some(false));
+}
+
+#[test]
+fn test_with_proxy() {
+    let client = Growatt::new().with_proxy("http://proxy.example:3128").unwrap();
+    assert!(client.connection.proxy.is_some());
+
+    let client = Growatt::new().with_proxy_auth("socks5://127.0.0.1:1080", "user", "secret").unwrap();
+    assert!(client.connection.proxy.is_some());
+
+    match Growatt::new().with_proxy("http://[not a host") {
+        Err(GrowattError::InvalidConfig(msg)) => assert!(msg.contains("proxy")),
+        other => panic!("expected InvalidConfig, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn test_watch_plants() {
+    use futures::StreamExt;
+
+    let mut server = mockito::Server::new_async().await;
+    let _roof = json_mock(&mut server, "POST", "/panel/getPlantData?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"id": "1", "plantName": "Roof", "currentPower": 1500}}"#)
+        .create_async()
+        .await;
+    let _shed = server
+        .mock("POST", "/panel/getPlantData?plantId=2")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let feed = client.watch_plants(vec!["1".to_string(), "2".to_string()], std::time::Duration::from_millis(10));
+    let readings: Vec<_> = feed.take(4).collect().await;
+
+    // Two ticks, each with one reading and one error, and the stream goes on
+    assert_eq!(readings.iter().filter(|reading| reading.is_err()).count(), 2);
+    for (plant_id, _) in readings.iter().filter_map(|reading| reading.as_ref().ok()) {
+        assert_eq!(plant_id, "1");
+    }
+}
+
+#[tokio::test]
+async fn test_user_agent() {
+    let mut server = mockito::Server::new_async().await;
+    let default_agent = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .match_header("user-agent", crate::DEFAULT_USER_AGENT)
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+    let custom_agent = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .match_header("user-agent", "my-monitor/1.0")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    client.get_plants().await.unwrap();
+    default_agent.assert_async().await;
+
+    let mut client = client.with_user_agent("my-monitor/1.0");
+    client.get_plants().await.unwrap();
+    custom_agent.assert_async().await;
+}
+
+#[test]
+fn test_builder() {
+    let client = Growatt::builder()
+        .base_url("https://openapi.growatt.com")
+        .credentials("primary", "secret")
+        .credentials("backup", "secret")
+        .timeout(std::time::Duration::from_secs(5))
+        .session_duration(std::time::Duration::from_secs(600))
+        .retry(3, std::time::Duration::from_millis(100))
+        .rate_limit(2.0)
+        .circuit_breaker(5, std::time::Duration::from_secs(30))
+        .proxy("http://127.0.0.1:3128")
+        .proxy_auth("user", "pass")
+        .user_agent("my-monitor/1.0")
+        .max_pages(10)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.base_url, "https://openapi.growatt.com");
+    assert_eq!(client.active_username(), Some("primary"));
+    assert_eq!(client.credentials.len(), 2);
+    assert_eq!(client.timeout, std::time::Duration::from_secs(5));
+    assert_eq!(client.session_duration, Duration::minutes(10));
+    assert!(client.retry_policy.is_some());
+    assert!(client.rate_limiter.is_some());
+    assert!(client.circuit_breaker().is_some());
+    assert!(client.connection.proxy.is_some());
+    assert_eq!(client.connection.user_agent.as_ref().unwrap(), "my-monitor/1.0");
+    assert_eq!(client.max_pages, 10);
+
+    // Unset options keep the defaults of `new`
+    let client = Growatt::builder().build().unwrap();
+    assert_eq!(client.base_url, "https://server.growatt.com");
+    assert_eq!(client.timeout, crate::DEFAULT_TIMEOUT);
+    assert!(client.retry_policy.is_none());
+}
+
+#[test]
+fn test_builder_validation() {
+    let invalid = [
+        Growatt::builder().base_url("not a url").build(),
+        Growatt::builder().timeout(std::time::Duration::ZERO).build(),
+        Growatt::builder().retry(0, std::time::Duration::from_secs(1)).build(),
+        Growatt::builder().rate_limit(f64::NAN).build(),
+        Growatt::builder().rate_limit(1e-300).build(),
+        Growatt::builder().retry_budget(-1.0).build(),
+        Growatt::builder().circuit_breaker(3, std::time::Duration::ZERO).build(),
+        Growatt::builder().proxy("not a proxy").build(),
+        Growatt::builder().proxy_auth("user", "pass").build(),
+        Growatt::builder().user_agent("bad\nagent").build(),
+        Growatt::builder().max_pages(0).build(),
+    ];
+    for result in invalid {
+        assert!(matches!(result, Err(GrowattError::InvalidConfig(_))));
+    }
+}
+
+#[tokio::test]
+async fn test_get_all_plant_data() {
+    let mut server = mockito::Server::new_async().await;
+    let _plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}, {"id": "2", "plantName": "Shed"}, {"id": "3", "plantName": "Barn"}]"#)
+        .create_async()
+        .await;
+    for plant_id in ["1", "3"] {
+        json_mock(&mut server, "POST", format!("/panel/getPlantData?plantId={}", plant_id).as_str())
+            .with_body(format!(r#"{{"result": 1, "obj": {{"id": "{}", "plantName": "Plant", "currentPower": 1500}}}}"#, plant_id))
+            .create_async()
+            .await;
+    }
+    let _shed = server
+        .mock("POST", "/panel/getPlantData?plantId=2")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url()).with_plant_concurrency(2);
+    let mut results = client.get_all_plant_data().await.unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // One failing plant doesn't abort the batch
+    assert_eq!(results.len(), 3);
+    assert!(results[0].1.is_ok());
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2].1.as_ref().unwrap().current_power, Some(1500.0));
+}
+
+#[test]
+fn test_device_page() {
+    use crate::DevicePage;
+
+    let page = DevicePage::from_response(&serde_json::json!({"obj": {"datas": [{"deviceSn": "A"}], "pages": "3", "count": 41}}), 1);
+    assert_eq!(page.devices.len(), 1);
+    assert_eq!((page.pages, page.count), (Some(3), Some(41)));
+    // A short page doesn't end the list
+    assert!(page.has_next());
+
+    // A zero total ends it, whatever the page count claims
+    let page = DevicePage::from_response(&serde_json::json!({"obj": {"datas": [{"deviceSn": "A"}], "pages": 3, "count": 0}}), 1);
+    assert!(!page.has_next());
+
+    // As do an empty page and a missing page count
+    assert!(!DevicePage::from_response(&serde_json::json!({"obj": {"datas": [], "pages": 3}}), 1).has_next());
+    assert!(!DevicePage::from_response(&serde_json::json!({"obj": {"datas": [{"deviceSn": "A"}]}}), 1).has_next());
+}
+
+#[tokio::test]
+async fn test_get_devices() {
+    let mut server = mockito::Server::new_async().await;
+    let _devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .with_body(r#"{"obj": {"pages": 1, "datas": [
+            {"deviceSn": "MIX1", "deviceType": "mix", "alias": "Garage", "deviceStatus": "1",
+             "lastUpdateTime": "2025-04-26 12:05:00", "datalogSn": "DL1", "pac": "3200.5"},
+            {"deviceSn": "TLX1", "deviceType": "tlx", "status": 0, "lost": "true", "datalogSn": "DL2",
+             "lastUpdateTime": 1745640300000},
+            {"deviceSn": "MAX1", "deviceType": "max", "alias": "", "power": 41000, "lastUpdateTime": ""},
+            {"alias": "no serial"}
+        ]}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let devices = client.get_devices("1").await.unwrap();
+    assert_eq!(devices.0.len(), 3);
+
+    let mix = devices.find("mix1").unwrap();
+    assert_eq!(mix.alias.as_deref(), Some("Garage"));
+    assert_eq!(mix.status, Some(1));
+    assert_eq!(mix.datalogger_sn.as_deref(), Some("DL1"));
+    assert_eq!(mix.power, Some(3200.5));
+    assert_eq!(mix.last_update_time, NaiveDate::from_ymd_opt(2025, 4, 26).unwrap().and_hms_opt(12, 5, 0));
+
+    let tlx = devices.find("TLX1").unwrap();
+    assert_eq!(tlx.state(), Some(DeviceState::Offline));
+    assert_eq!(tlx.power, None);
+    // An epoch timestamp is dropped, not the device
+    assert_eq!(tlx.last_update_time, None);
+
+    let max = &devices.of_type("MAX")[0];
+    assert_eq!(max.device_sn, "MAX1");
+    assert_eq!(max.power, Some(41000.0));
+    assert_eq!(max.last_update_time, None);
+    assert_eq!(max.datalogger_sn, None);
+}
+
+#[tokio::test]
+async fn test_stream_plant_devices() {
+    use futures::StreamExt;
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let first = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .match_body(Matcher::UrlEncoded("currPage".into(), "1".into()))
+        .with_body(r#"{"result": 1, "obj": {"datas": [{"deviceSn": "A"}, {"deviceSn": "B"}], "pages": 2, "count": 3}}"#)
+        .create_async()
+        .await;
+    let second = json_mock(&mut server, "POST", "/panel/getDevicesByPlantList")
+        .match_body(Matcher::UrlEncoded("currPage".into(), "2".into()))
+        .with_body(r#"{"result": 1, "obj": {"datas": [{"deviceSn": "C"}], "pages": 2, "count": 3}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let mut devices = client.stream_plant_devices("1");
+    // Only the first page is fetched for the first device
+    assert_eq!(devices.next().await.unwrap().unwrap()["deviceSn"], "A");
+    first.assert_async().await;
+    let rest: Vec<_> = devices.collect().await;
+    assert_eq!(rest.len(), 2);
+    second.assert_async().await;
+
+    let devices = client.get_all_devices_by_plant("1").await.unwrap();
+    let serials: Vec<_> = devices.iter().map(|device| device["deviceSn"].as_str().unwrap()).collect();
+    assert_eq!(serials, ["A", "B", "C"]);
+}
+
+#[tokio::test]
+async fn test_save_and_load_session() {
+    let mut server = mockito::Server::new_async().await;
+    let _login = json_mock(&mut server, "POST", "/login")
+        .with_header("set-cookie", "JSESSIONID=abc123; Path=/")
+        .with_body(r#"{"result": 1, "token": "tok"}"#)
+        .create_async()
+        .await;
+    let plants = json_mock(&mut server, "POST", "/index/getPlantListTitle")
+        .match_header("cookie", "JSESSIONID=abc123")
+        .with_body(r#"[{"id": "1", "plantName": "Roof"}]"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new().with_base_url(&server.url());
+    client.login("user", "secret").await.unwrap();
+    let path = env::temp_dir().join(format!("growatt-session-{}.json", std::process::id()));
+    client.save_session(&path).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // The restored client skips the login: without credentials it could not log in
+    let mut restored = Growatt::load_session(&path).unwrap().with_base_url(&server.url());
+    std::fs::remove_file(&path).unwrap();
+    assert!(restored.is_session_valid());
+    assert_eq!(restored.get_token(), Some("tok".to_string()));
+    restored.get_plants().await.unwrap();
+    plants.assert_async().await;
+
+    assert!(matches!(Growatt::load_session(&path), Err(GrowattError::Io(_))));
+}
+
+#[tokio::test]
+async fn test_login_captcha() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _challenge = json_mock(&mut server, "POST", "/login")
+        .match_body(Matcher::UrlEncoded("validateCode".into(), "".into()))
+        .with_body(r#"{"result": 0, "msg": "Please enter the verification code", "validateCodeUrl": "/validateCode.do"}"#)
+        .create_async()
+        .await;
+    let _answered = json_mock(&mut server, "POST", "/login")
+        .match_body(Matcher::UrlEncoded("validateCode".into(), "x7k2".into()))
+        .with_body(r#"{"result": 1}"#)
+        .create_async()
+        .await;
+
+    let mut client = Growatt::new().with_base_url(&server.url());
+    match client.login("user", "secret").await {
+        Err(GrowattError::CaptchaRequired { captcha }) => assert_eq!(captcha.as_deref(), Some("/validateCode.do")),
+        other => panic!("expected CaptchaRequired, got {:?}", other),
+    }
+
+    client.login_with_captcha("user", "secret", "x7k2").await.unwrap();
+    assert!(client.is_session_valid());
+}
+
+#[test]
+fn test_login_failure_mapping() {
+    use crate::auth::login_failure;
+
+    let flagged = login_failure(&serde_json::json!({"result": 0, "needValidateCode": "1"}));
+    assert!(matches!(flagged, GrowattError::CaptchaRequired { captcha: None }));
+
+    let rejected = login_failure(&serde_json::json!({"result": 0, "msg": "Wrong password"}));
+    assert!(matches!(rejected, GrowattError::AuthError(msg) if msg == "Wrong password"));
+
+    let cases = [
+        (serde_json::json!({"result": -1, "msg": "error"}), ErrorKind::InvalidCredentials),
+        (serde_json::json!({"result": "-1"}), ErrorKind::InvalidCredentials),
+        (serde_json::json!({"result": 0, "msg": "502"}), ErrorKind::InvalidCredentials),
+        (serde_json::json!({"result": -2, "msg": "locked"}), ErrorKind::AccountLocked),
+        (serde_json::json!({"result": -3, "msg": "frozen"}), ErrorKind::AccountFrozen),
+        (serde_json::json!({"result": 0, "msg": "501"}), ErrorKind::AccountNotFound),
+        (serde_json::json!({"result": 0, "msg": "Account does not exist"}), ErrorKind::AccountNotFound),
+        (serde_json::json!({"result": -9, "msg": "new code"}), ErrorKind::Auth),
+    ];
+    for (response, kind) in cases {
+        assert_eq!(login_failure(&response).kind(), kind, "{}", response);
+    }
+}
+
+#[test]
+fn test_energy_chart() {
+    use crate::EnergyChart;
+
+    let response = serde_json::json!({"result": 1, "obj": {
+        "charts": {"ppv": ["12.5", null, 8], "pacToGrid": [1, 2, 3], "pself": [0.5, null, 1]},
+        "eCharge": "4.2"
+    }});
+    let chart = EnergyChart::try_from(&response).unwrap();
+    assert_eq!(chart.ppv, Some(vec![Some(12.5), None, Some(8.0)]));
+    assert_eq!(chart.pac_to_grid, Some(vec![Some(1.0), Some(2.0), Some(3.0)]));
+    // Series the chart left out stay None rather than empty
+    assert_eq!(chart.pac_to_user, None);
+    assert_eq!(chart.other["pself"], vec![Some(0.5), None, Some(1.0)]);
+    assert_eq!(chart.e_charge, Some(4.2));
+
+    assert!(matches!(
+        EnergyChart::from_response(&serde_json::json!({"result": 0})),
+        Err(GrowattError::InvalidResponse(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_energy_stats_monthly_typed() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _month = json_mock(&mut server, "POST", "/panel/mix/getMIXEnergyMonthChart")
+        .match_body(Matcher::UrlEncoded("date".into(), "2025-04".into()))
+        .with_body(r#"{"result": 1, "obj": {"charts": {"ppv": ["12.5", null]}}}"#)
+        .create_async()
+        .await;
+
+    // The anchor formats the month the way the endpoint expects
+    let mut client = logged_in_client(&server.url());
+    let month = ChartMonth(NaiveDate::from_ymd_opt(2025, 4, 26).unwrap());
+    let chart = client.get_energy_stats_monthly_typed(month, "1", "MIX1").await.unwrap();
+    assert_eq!(chart.ppv, Some(vec![Some(12.5), None]));
+}
+
+#[tokio::test]
+async fn test_get_energy_range_daily() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _day1 = json_mock(&mut server, "POST", "/panel/mix/getMIXEnergyDayChart")
+        .match_body(Matcher::UrlEncoded("date".into(), "2025-04-25".into()))
+        .with_body(r#"{"result": 1, "obj": {"charts": {"ppv": [null, "1.5", "2.5"]}}}"#)
+        .create_async()
+        .await;
+    let _day2 = json_mock(&mut server, "POST", "/panel/mix/getMIXEnergyDayChart")
+        .match_body(Matcher::UrlEncoded("date".into(), "2025-04-26".into()))
+        .with_body(r#"{"result": 1, "obj": {"charts": {"ppv": [null, null], "sysOut": []}}}"#)
+        .create_async()
+        .await;
+    let _day3 = json_mock(&mut server, "POST", "/panel/mix/getMIXEnergyDayChart")
+        .match_body(Matcher::UrlEncoded("date".into(), "2025-04-27".into()))
+        .with_body(r#"{"result": 1, "obj": {"charts": {"ppv": [3, 4]}}}"#)
+        .create_async()
+        .await;
+
+    let day = |d| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+    let mut client = logged_in_client(&server.url());
+    let charts = client.get_energy_range_daily(day(25), day(27), "1", "MIX1").await.unwrap();
+
+    // The empty day is skipped and the order follows the dates
+    assert_eq!(charts.len(), 2);
+    assert_eq!(charts[0].0, day(25));
+    assert_eq!(charts[0].1.ppv, Some(vec![None, Some(1.5), Some(2.5)]));
+    assert_eq!(charts[1].0, day(27));
+
+    // Filled point by point from the neighbouring days' charts
+    let charts = client.get_energy_range_daily_filled(day(25), day(27), "1", "MIX1", GapFill::Interpolate).await.unwrap();
+    assert_eq!(charts.len(), 3);
+    assert_eq!(charts[1].0, day(26));
+    assert_eq!(charts[1].1.ppv, Some(vec![Some(3.0), Some(2.75), Some(2.5)]));
+    assert_eq!(charts[2].1.ppv, Some(vec![Some(3.0), Some(4.0)]));
+
+    let charts = client.get_energy_range_daily_filled(day(25), day(27), "1", "MIX1", GapFill::Zero).await.unwrap();
+    assert_eq!(charts[1].1.ppv, Some(vec![Some(0.0); 3]));
+
+    assert!(matches!(
+        client.get_energy_range_daily(day(27), day(25), "1", "MIX1").await,
+        Err(GrowattError::InvalidInput(_))
+    ));
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_energy_chart_to_csv() {
+    use crate::{energy_chart_to_csv, EnergyChart};
+
+    let response = serde_json::json!({"result": 1, "obj": {
+        "charts": {"ppv": ["12.5", null, 8], "pacToGrid": [1, 2], "pself": [0.5, null, 1, 2]}
+    }});
+    let chart = EnergyChart::try_from(&response).unwrap();
+
+    let mut out = Vec::new();
+    energy_chart_to_csv(&chart, &mut out).unwrap();
+    let csv = String::from_utf8(out).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(rows[0], "ppv,pacToGrid,pself");
+    assert_eq!(rows[1], "12.5,1,0.5");
+    assert_eq!(rows[2], ",2,");
+    // Shorter series are padded up to the longest
+    assert_eq!(rows[4], ",,2");
+    assert_eq!(rows.len(), 5);
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn test_mock_growatt_api() {
+    use crate::{GrowattApi, MockGrowattApi};
+
+    // Downstream code written against the trait rather than the client
+    async fn pv_today(api: &mut impl GrowattApi, plant_id: &str) -> crate::Result<f64> {
+        let mut total = 0.0;
+        for mix in api.get_devices(plant_id).await?.of_type("mix") {
+            total += api.get_mix_total(plant_id, &mix.device_sn).await?.epv_today.unwrap_or(0.0);
+        }
+        Ok(total)
+    }
+
+    let devices: crate::DeviceList = serde_json::from_value(serde_json::json!([
+        {"deviceSn": "MIX1", "deviceType": "mix"},
+        {"deviceSn": "MIX2", "deviceType": "mix"},
+        {"deviceSn": "ENV1", "deviceType": "env"}
+    ]))
+    .unwrap();
+
+    let mut mock = MockGrowattApi::new();
+    mock.expect_get_devices()
+        .withf(|plant_id| plant_id == "1")
+        .times(1)
+        .returning(move |_| Box::pin(std::future::ready(Ok(devices.clone()))));
+    mock.expect_get_mix_total()
+        .times(2)
+        .returning(|_, mix_sn| {
+            Box::pin(std::future::ready(Ok(MixTotalData {
+                epv_today: Some(if mix_sn == "MIX1" { 12.5 } else { 4.0 }),
+                ..Default::default()
+            })))
+        });
+
+    // The trait's futures are Send, so generic code can run on a spawned task
+    let total = tokio::spawn(async move { pv_today(&mut mock, "1").await });
+    assert_eq!(total.await.unwrap().unwrap(), 16.5);
+
+    let mut failing = MockGrowattApi::new();
+    failing
+        .expect_get_devices()
+        .returning(|_| Box::pin(std::future::ready(Err(GrowattError::NotLoggedIn))));
+    assert!(matches!(pv_today(&mut failing, "1").await, Err(GrowattError::NotLoggedIn)));
+}
+
+#[test]
+fn test_session_expiry_accessors() {
+    let mut client = Growatt::new();
+    assert_eq!(client.session_expiry(), None);
+    assert_eq!(client.time_until_expiry(), None);
+    assert!(!client.is_session_valid());
+
+    let expiry = Utc::now() + Duration::minutes(10);
+    client.session_expiry = Some(expiry);
+    assert_eq!(client.session_expiry(), Some(expiry));
+    let left = client.time_until_expiry().unwrap();
+    assert!(left > Duration::minutes(9) && left <= Duration::minutes(10));
+    assert!(client.is_session_valid());
+
+    // An expired session reports no time left rather than a negative duration
+    client.session_expiry = Some(Utc::now() - Duration::minutes(1));
+    assert_eq!(client.time_until_expiry(), Some(Duration::zero()));
+    assert!(!client.is_session_valid());
+}
+
+#[tokio::test]
+async fn test_call_raw() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _endpoint = json_mock(&mut server, "POST", "/panel/getUnwrappedThing?plantId=1")
+        .match_body(Matcher::UrlEncoded("deviceSn".into(), "ABC".into()))
+        .with_body(r#"{"result": 1, "obj": {"value": 42}}"#)
+        .create_async()
+        .await;
+    let _empty = json_mock(&mut server, "POST", "/panel/empty")
+        .with_body("{}")
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let response = client
+        .call_raw("/panel/getUnwrappedThing?plantId=1", &[("deviceSn", "ABC")])
+        .await
+        .unwrap();
+    assert_eq!(response["obj"]["value"], 42);
+
+    // Empty bodies are caught in one place for every endpoint
+    assert!(matches!(
+        client.call_raw("/panel/empty", &[]).await,
+        Err(GrowattError::InvalidResponse(msg)) if msg.starts_with("Empty response")
+    ));
+}
+
+#[tokio::test]
+async fn test_tlx_endpoints() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _total = json_mock(&mut server, "POST", "/panel/tlx/getTLXTotalData?plantId=1")
+        .match_body(Matcher::UrlEncoded("tlxSn".into(), "TLX1".into()))
+        .with_body(r#"{"result": 1, "obj": {"eacToday": "12.3", "epvTotal": 4567, "eToGridTotal": "--"}}"#)
+        .create_async()
+        .await;
+    let _status = json_mock(&mut server, "POST", "/panel/tlx/getTLXStatusData?plantId=1")
+        .match_body(Matcher::UrlEncoded("tlxSn".into(), "TLX1".into()))
+        .with_body(r#"{"result": 1, "obj": {"ppv": "3.2", "pac": 3.1, "bdc1Soc": "80", "status": "1"}}"#)
+        .create_async()
+        .await;
+    let _devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlant?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"tlx": [["TLX1", "Garage"]], "mix": null, "max": []}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let total = client.get_tlx_total("1", "TLX1").await.unwrap();
+    assert_eq!(total.eac_today, Some(12.3));
+    assert_eq!(total.epv_total, Some(4567.0));
+    assert_eq!(total.e_to_grid_total, None);
+
+    let status = client.get_tlx_status("1", "TLX1").await.unwrap();
+    assert_eq!(status.ppv, Some(3.2));
+    assert_eq!(status.soc, Some(80.0));
+    assert_eq!(status.status, Some(1));
+
+    let devices = client.get_devices_by_type("1").await.unwrap();
+    assert_eq!(devices.tlx.len(), 1);
+    assert!(devices.mix.is_empty() && devices.max.is_empty() && devices.storage.is_empty());
+}
+
+#[tokio::test]
+async fn test_storage_endpoints() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _devices = json_mock(&mut server, "POST", "/panel/getDevicesByPlant?plantId=1")
+        .with_body(r#"{"result": 1, "obj": {"storage": [["SPF1", "Shed"]]}}"#)
+        .create_async()
+        .await;
+    let _total = json_mock(&mut server, "POST", "/panel/storage/getStorageTotalData?plantId=1")
+        .match_body(Matcher::UrlEncoded("storageSn".into(), "SPF1".into()))
+        .with_body(r#"{"result": 1, "obj": {"eChargeToday": "2.5", "useEnergyToday": 8}}"#)
+        .create_async()
+        .await;
+    let _status = json_mock(&mut server, "POST", "/panel/storage/getStorageStatusData?plantId=1")
+        .match_body(Matcher::UrlEncoded("storageSn".into(), "SPF1".into()))
+        .with_body(r#"{"result": 1, "obj": {"capacity": "76", "pCharge": "450", "outPutPower": 320, "panelPower": "780"}}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    let device_types = client.classify_plant_devices("1").await.unwrap();
+    assert_eq!(device_types["SPF1"], DeviceType::Storage);
+
+    let total = client.get_storage_total("1", "SPF1").await.unwrap();
+    assert_eq!(total.e_charge_today, Some(2.5));
+    assert_eq!(total.use_energy_today, Some(8.0));
+
+    let status = client.get_storage_status("1", "SPF1").await.unwrap();
+    assert_eq!(status.soc, Some(76.0));
+    assert_eq!(status.charge_power, Some(450.0));
+    assert_eq!(status.load_power, Some(320.0));
+    assert_eq!(status.ppv, Some(780.0));
+}
+
+#[tokio::test]
+async fn test_set_inverter_param() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let write = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("action".into(), "mixSet".into()),
+            Matcher::UrlEncoded("serialNum".into(), "MIX1".into()),
+            Matcher::UrlEncoded("type".into(), "pv_active_p_rate".into()),
+            Matcher::UrlEncoded("param1".into(), "80".into()),
+        ]))
+        .with_body(r#"{"success": true}"#)
+        .create_async()
+        .await;
+    let _clock = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::UrlEncoded("type".into(), "pf_sys_year".into()))
+        .with_body(r#"{"success": false, "msg": "offline"}"#)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(client.set_inverter_param("MIX1", "pv_active_p_rate", &[("param1", "80")]).await.unwrap());
+    write.assert_async().await;
+
+    assert!(!client.sync_mix_time_now("MIX1").await.unwrap());
+    let response = client.post_mix_ac_discharge_time_period_now("1", "MIX1").await.unwrap();
+    assert_eq!(response["msg"], "offline");
+}
+
+#[tokio::test]
+async fn test_set_mix_charge_schedule() {
+    use mockito::Matcher;
+
+    let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+    let mut schedule = ChargeSchedule {
+        charge_rate: 80,
+        target_soc: 90,
+        ac_charge: true,
+        battery_first_periods: vec![TimePeriod { start: time(1, 30), end: time(5, 0), enabled: true }],
+        discharge_rate: 60,
+        discharge_stop_soc: 20,
+        grid_first_periods: vec![TimePeriod { start: time(17, 0), end: time(19, 45), enabled: true }],
+    };
+
+    let mut server = mockito::Server::new_async().await;
+    let battery_first = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("serialNum".into(), "MIX1".into()),
+            Matcher::UrlEncoded("type".into(), "mix_ac_charge_time_period".into()),
+            Matcher::UrlEncoded("param1".into(), "80".into()),
+            Matcher::UrlEncoded("param2".into(), "90".into()),
+            Matcher::UrlEncoded("param3".into(), "1".into()),
+            Matcher::UrlEncoded("param4".into(), "1".into()),
+            Matcher::UrlEncoded("param5".into(), "30".into()),
+            Matcher::UrlEncoded("param6".into(), "5".into()),
+            Matcher::UrlEncoded("param7".into(), "0".into()),
+            Matcher::UrlEncoded("param8".into(), "1".into()),
+            Matcher::UrlEncoded("param13".into(), "0".into()),
+        ]))
+        .with_body(r#"{"success": true}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let grid_first = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("serialNum".into(), "MIX1".into()),
+            Matcher::UrlEncoded("type".into(), "mix_ac_discharge_time_period".into()),
+            Matcher::UrlEncoded("param1".into(), "60".into()),
+            Matcher::UrlEncoded("param2".into(), "20".into()),
+            Matcher::UrlEncoded("param3".into(), "17".into()),
+            Matcher::UrlEncoded("param4".into(), "0".into()),
+            Matcher::UrlEncoded("param5".into(), "19".into()),
+            Matcher::UrlEncoded("param6".into(), "45".into()),
+            Matcher::UrlEncoded("param7".into(), "1".into()),
+            Matcher::UrlEncoded("param12".into(), "0".into()),
+        ]))
+        .with_body(r#"{"success": true}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut client = logged_in_client(&server.url());
+    assert!(client.set_mix_charge_schedule("MIX1", &schedule).await.unwrap());
+
+    // Invalid schedules are rejected without another request
+    schedule.battery_first_periods[0].end = time(1, 0);
+    let err = client.set_mix_charge_schedule("MIX1", &schedule).await.unwrap_err();
+    assert!(matches!(&err, GrowattError::InvalidInput(message) if message.contains("01:30-01:00")));
+    schedule.battery_first_periods.clear();
+
+    schedule.grid_first_periods[0].start = time(20, 0);
+    let err = client.set_mix_charge_schedule("MIX1", &schedule).await.unwrap_err();
+    assert!(matches!(&err, GrowattError::InvalidInput(message) if message.contains("grid-first")));
+
+    schedule.grid_first_periods = vec![schedule.grid_first_periods[0].clone(); 4];
+    schedule.grid_first_periods.iter_mut().for_each(|period| period.enabled = false);
+    assert!(client.set_mix_charge_schedule("MIX1", &schedule).await.is_err());
+
+    schedule.grid_first_periods.clear();
+    schedule.discharge_stop_soc = 101;
+    assert!(client.set_mix_charge_schedule("MIX1", &schedule).await.is_err());
+
+    battery_first.assert_async().await;
+    grid_first.assert_async().await;
+
+    // A rejected grid-first half is reported as a partial write
+    let mut server = mockito::Server::new_async().await;
+    let _battery_first = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::UrlEncoded("type".into(), "mix_ac_charge_time_period".into()))
+        .with_body(r#"{"success": true}"#)
+        .create_async()
+        .await;
+    let _grid_first = json_mock(&mut server, "POST", "/tcpSet.do")
+        .match_body(Matcher::UrlEncoded("type".into(), "mix_ac_discharge_time_period".into()))
+        .with_body(r#"{"success": false}"#)
+        .create_async()
+        .await;
+
+    schedule.discharge_stop_soc = 20;
+    let mut client = logged_in_client(&server.url());
+    match client.set_mix_charge_schedule("MIX1", &schedule).await {
+        Err(GrowattError::PartiallyApplied { applied, failed, source: None }) => {
+            assert_eq!(applied, "battery-first");
+            assert_eq!(failed, "grid-first");
+        }
+        other => panic!("expected PartiallyApplied, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_get_device_event_log() {
+    use mockito::Matcher;
+
+    let mut server = mockito::Server::new_async().await;
+    let _page1 = json_mock(&mut server, "POST", "/log/getDeviceOperationLog")
+        .match_body(Matcher::UrlEncoded("toPageNum".into(), "1".into()))
+        .with_body(r#"{"result": 1, "obj": {"pages": 2, "datas": [
+            {"time": "2025-04-26 08:00:00", "operType": "Setting", "content": "Export limit 50%"}
+        ]}}"#)
+        .create_async()
+        .await;
+    let _page2 = json_mock(&mut server, "POST", "/log/getDeviceOperationLog")
+        .match_body(Matcher::UrlEncoded("toPageNum".into(), "2".into()))
+        .with_body(r#"{"result": 1, "obj": {"pages": 2, "datas": [
+            {"time": "2025-04-27 09:30:00", "type": "Mode", "content": "Battery first"}
+        ]}}"#)
+        .create_async()
+        .await;
+
+    let day = |d| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+    let mut client = logged_in_client(&server.url());
+    let events = client.get_device_event_log("INV1", day(26), day(27)).await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event_type, "Setting");
+    assert_eq!(events[1].description, "Battery first");
+
+    assert!(matches!(
+        client.get_device_event_log("INV1", day(27), day(26)).await,
+        Err(GrowattError::InvalidInput(_))
+    ));
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::{deserialize_lenient_f64, deserialize_lenient_i64, from_result_value};
+use crate::{Growatt, GrowattError, Result};
+
+/// Totals reported by `getTLXTotalData` for a TLX (MIN) inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TlxTotalData {
+    /// AC energy output today (kWh).
+    #[serde(rename = "eacToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub eac_today: Option<f64>,
+    /// AC energy output over the inverter's lifetime (kWh).
+    #[serde(rename = "eacTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub eac_total: Option<f64>,
+    /// PV energy generated today (kWh).
+    #[serde(rename = "epvToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_today: Option<f64>,
+    /// PV energy generated over the inverter's lifetime (kWh).
+    #[serde(rename = "epvTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub epv_total: Option<f64>,
+    /// Energy charged into the battery today, on models with one (kWh).
+    #[serde(rename = "eChargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_charge_today: Option<f64>,
+    /// Energy discharged from the battery today, on models with one (kWh).
+    #[serde(rename = "eDischargeToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_discharge_today: Option<f64>,
+    /// Energy consumed by the local load today (kWh).
+    #[serde(rename = "elocalLoadToday", default, deserialize_with = "deserialize_lenient_f64")]
+    pub elocal_load_today: Option<f64>,
+    /// Energy consumed by the local load over the inverter's lifetime (kWh).
+    #[serde(rename = "elocalLoadTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub elocal_load_total: Option<f64>,
+    /// Energy exported to the grid over the inverter's lifetime (kWh).
+    #[serde(rename = "eToGridTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_to_grid_total: Option<f64>,
+    /// Energy imported from the grid over the inverter's lifetime (kWh).
+    #[serde(rename = "eToUserTotal", default, deserialize_with = "deserialize_lenient_f64")]
+    pub e_to_user_total: Option<f64>,
+}
+
+/// Converts a raw total result, or a full response with the totals under `obj`.
+impl TryFrom<&serde_json::Value> for TlxTotalData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+/// Real-time status reported by `getTLXStatusData` for a TLX (MIN) inverter.
+///
+/// Numbers arrive as either numbers or strings and are coerced; missing or
+/// non-numeric values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TlxStatusData {
+    /// PV input power (kW).
+    #[serde(default, deserialize_with = "deserialize_lenient_f64")]
+    pub ppv: Option<f64>,
+    /// AC output power (kW).
+    #[serde(default, deserialize_with = "deserialize_lenient_f64")]
+    pub pac: Option<f64>,
+    /// Power imported from the grid (kW).
+    #[serde(rename = "pactouser", default, deserialize_with = "deserialize_lenient_f64")]
+    pub pactouser: Option<f64>,
+    /// Power exported to the grid (kW).
+    #[serde(rename = "pactogrid", default, deserialize_with = "deserialize_lenient_f64")]
+    pub pactogrid: Option<f64>,
+    /// Power used by the local load (kW).
+    #[serde(rename = "pLocalLoad", default, deserialize_with = "deserialize_lenient_f64")]
+    pub p_local_load: Option<f64>,
+    /// Battery state of charge, on models with one (%).
+    #[serde(default, alias = "bdc1Soc", deserialize_with = "deserialize_lenient_f64")]
+    pub soc: Option<f64>,
+    /// Grid voltage (V).
+    #[serde(rename = "vAc1", default, deserialize_with = "deserialize_lenient_f64")]
+    pub v_ac1: Option<f64>,
+    /// Grid frequency (Hz).
+    #[serde(rename = "fAc", default, deserialize_with = "deserialize_lenient_f64")]
+    pub f_ac: Option<f64>,
+    /// Raw run state code.
+    #[serde(default, deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+}
+
+/// Converts a raw status result, or a full response with the status under `obj`.
+impl TryFrom<&serde_json::Value> for TlxStatusData {
+    type Error = GrowattError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self> {
+        from_result_value(value)
+    }
+}
+
+impl Growatt {
+    /// Fetches a TLX (MIN) inverter's energy totals, the TLX counterpart of
+    /// [`Growatt::get_mix_total`].
+    pub async fn get_tlx_total(&mut self, plant_id: &str, tlx_sn: &str) -> Result<TlxTotalData> {
+        let obj = self.post_plant_form("/panel/tlx/getTLXTotalData", plant_id, &[("tlxSn", tlx_sn)]).await?;
+        TlxTotalData::try_from(&obj)
+    }
+
+    /// Fetches a TLX (MIN) inverter's real-time status, the TLX counterpart of
+    /// [`Growatt::get_mix_status_typed`].
+    pub async fn get_tlx_status(&mut self, plant_id: &str, tlx_sn: &str) -> Result<TlxStatusData> {
+        let obj = self.post_plant_form("/panel/tlx/getTLXStatusData", plant_id, &[("tlxSn", tlx_sn)]).await?;
+        TlxStatusData::try_from(&obj)
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{PlantData, Weather};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PowerUnit {
+    #[default]
+    Watt,
+    Kilowatt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EnergyUnit {
+    WattHour,
+    #[default]
+    KilowattHour,
+    MegawattHour,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+/// The display units configured for the account, which the portal uses for
+/// the values it returns. Units the login response doesn't mention default to
+/// the portal's defaults: W, kWh and °C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AccountUnits {
+    pub power: PowerUnit,
+    pub energy: EnergyUnit,
+    pub temperature: TemperatureUnit,
+}
+
+impl AccountUnits {
+    // Read from the login response's user object
+    pub(crate) fn from_value(json_response: &serde_json::Value) -> Self {
+        let user = json_response.get("user");
+        let unit = |key: &str| {
+            user.and_then(|user| user.get(key))
+                .and_then(|unit| unit.as_str())
+                .map(|unit| unit.trim().to_lowercase())
+        };
+
+        let power = match unit("powerUnit").as_deref() {
+            Some("kw") => PowerUnit::Kilowatt,
+            _ => PowerUnit::Watt,
+        };
+        let energy = match unit("energyUnit").as_deref() {
+            Some("wh") => EnergyUnit::WattHour,
+            Some("mwh") => EnergyUnit::MegawattHour,
+            _ => EnergyUnit::KilowattHour,
+        };
+        let temperature = match unit("temperatureUnit").as_deref() {
+            Some("℉" | "°f" | "f") => TemperatureUnit::Fahrenheit,
+            _ => TemperatureUnit::Celsius,
+        };
+
+        Self {
+            power,
+            energy,
+            temperature,
+        }
+    }
+
+    pub fn to_watts(&self, power: f64) -> f64 {
+        match self.power {
+            PowerUnit::Watt => power,
+            PowerUnit::Kilowatt => power * 1000.0,
+        }
+    }
+
+    pub fn to_watt_hours(&self, energy: f64) -> f64 {
+        match self.energy {
+            EnergyUnit::WattHour => energy,
+            EnergyUnit::KilowattHour => energy * 1000.0,
+            EnergyUnit::MegawattHour => energy * 1_000_000.0,
+        }
+    }
+
+    pub fn to_celsius(&self, temperature: f64) -> f64 {
+        match self.temperature {
+            TemperatureUnit::Celsius => temperature,
+            TemperatureUnit::Fahrenheit => (temperature - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+impl PlantData {
+    /// Converts energies to Wh from the account's units.
+    ///
+    /// Powers are left alone: [`PlantData::normalize_units`] already puts them
+    /// in W.
+    pub fn to_si(&mut self, units: &AccountUnits) {
+        self.today_energy = self.today_energy.map(|energy| units.to_watt_hours(energy));
+        self.total_energy = self.total_energy.map(|energy| units.to_watt_hours(energy));
+    }
+}
+
+impl Weather {
+    /// Converts temperatures to °C from the account's units.
+    pub fn to_si(&mut self, units: &AccountUnits) {
+        let to_celsius = |temperature: Option<f64>| temperature.map(|t| units.to_celsius(t));
+
+        self.temperature = to_celsius(self.temperature);
+        for day in &mut self.forecast {
+            day.temp_high = to_celsius(day.temp_high);
+            day.temp_low = to_celsius(day.temp_low);
+        }
+    }
+}
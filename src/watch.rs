@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use futures::stream::{self, Stream, StreamExt};
+use rand::Rng;
+
+use crate::{Growatt, PlantData, Result};
+
+/// How many plants a multi-plant watch fetches at once on each tick.
+const WATCH_CONCURRENCY: usize = 8;
+
+/// When a polling stream fetches: a fixed interval, optionally spread out by
+/// a random jitter so many clients on the same schedule don't hit the server
+/// at the same instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollSchedule {
+    interval: Duration,
+    jitter: Duration,
+}
+
+impl PollSchedule {
+    /// Polls every `interval`, without jitter.
+    pub fn every(interval: Duration) -> Self {
+        Self {
+            interval,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// Adds a random delay of up to `window` to every tick, and delays the
+    /// first poll by a random amount within the window too.
+    pub fn with_jitter(mut self, window: Duration) -> Self {
+        self.jitter = window;
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    // A random delay within the jitter window
+    fn random_jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            rand::thread_rng().gen_range(Duration::ZERO..self.jitter)
+        }
+    }
+
+    pub(crate) fn first_delay(&self) -> Duration {
+        self.random_jitter()
+    }
+
+    pub(crate) fn next_delay(&self) -> Duration {
+        self.interval + self.random_jitter()
+    }
+}
+
+impl From<Duration> for PollSchedule {
+    fn from(interval: Duration) -> Self {
+        Self::every(interval)
+    }
+}
+
+impl Growatt {
+    /// Polls a plant's data on a schedule, yielding each reading.
+    ///
+    /// The stream never ends on its own; errors are yielded as items and
+    /// polling continues at the next tick. Pass a [`Duration`] for a plain
+    /// interval or a [`PollSchedule`] to add jitter.
+    pub fn watch_plant<'a>(
+        &'a mut self,
+        plant_id: &'a str,
+        schedule: impl Into<PollSchedule>,
+    ) -> impl Stream<Item = Result<PlantData>> + 'a {
+        let schedule = schedule.into();
+
+        stream::unfold((self, true), move |(client, first)| async move {
+            let delay = if first { schedule.first_delay() } else { schedule.next_delay() };
+            tokio::time::sleep(delay).await;
+
+            let reading = client.get_plant(plant_id).await;
+            Some((reading, (client, false)))
+        })
+    }
+
+    /// Polls several plants on a shared schedule, yielding each plant's
+    /// reading tagged with its ID.
+    ///
+    /// On every tick the plants are fetched concurrently and readings are
+    /// yielded in the order they arrive. Like [`Growatt::watch_plant`] the
+    /// stream never ends on its own: a plant that fails yields an `Err` item
+    /// and is polled again at the next tick.
+    pub fn watch_plants(
+        &mut self,
+        plant_ids: Vec<String>,
+        schedule: impl Into<PollSchedule>,
+    ) -> impl Stream<Item = Result<(String, PlantData)>> + '_ {
+        let schedule = schedule.into();
+
+        stream::unfold((self, plant_ids, true), move |(client, plant_ids, first)| async move {
+            let delay = if first { schedule.first_delay() } else { schedule.next_delay() };
+            tokio::time::sleep(delay).await;
+
+            // OpenAPI requests carry their token instead of a session
+            let session = if client.uses_openapi() { Ok(()) } else { client.check_login().await };
+
+            let readings: Vec<Result<(String, PlantData)>> = match session {
+                Ok(()) => {
+                    let this = &*client;
+                    stream::iter(&plant_ids)
+                        .map(|plant_id| async move {
+                            let reading = if this.uses_openapi() {
+                                this.openapi_get_plant(plant_id).await
+                            } else {
+                                this.fetch_plant(plant_id).await
+                            };
+                            reading.map(|data| (plant_id.clone(), data))
+                        })
+                        .buffer_unordered(WATCH_CONCURRENCY)
+                        .collect()
+                        .await
+                }
+                Err(e) => vec![Err(e)],
+            };
+
+            Some((stream::iter(readings), (client, plant_ids, false)))
+        })
+        .flatten()
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::{deserialize_lenient_datetime, deserialize_lenient_f64, deserialize_lenient_i64};
+use crate::{check_plant_access, response_obj, value_as_f64, Growatt, GrowattError, Result};
+
+/// Weather at the plant's location, as shown in the portal's weather widget.
+///
+/// This is the location-based weather service, not the plant's own
+/// environmental sensors (see [`Growatt::get_weather`] for those).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    /// Current temperature, in °C unless the account displays °F.
+    pub temperature: Option<f64>,
+    pub condition: Option<String>,
+    /// Relative humidity in %.
+    pub humidity: Option<f64>,
+    pub forecast: Vec<ForecastEntry>,
+}
+
+/// One day of the weather forecast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastEntry {
+    pub date: NaiveDate,
+    pub condition: Option<String>,
+    /// Highest temperature of the day.
+    pub temp_high: Option<f64>,
+    /// Lowest temperature of the day.
+    pub temp_low: Option<f64>,
+    /// Expected solar irradiance (W/m²), when the forecast includes it.
+    pub irradiance: Option<f64>,
+}
+
+/// An environmental monitor installed at the plant, from
+/// [`Growatt::get_env_sensors`].
+///
+/// Sensors differ in what they measure, so every reading is optional.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvSensor {
+    #[serde(rename = "deviceSn", alias = "envSn", alias = "sn")]
+    pub device_sn: String,
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(rename = "datalogSn", default)]
+    pub datalogger_sn: Option<String>,
+    #[serde(default, alias = "deviceStatus", deserialize_with = "deserialize_lenient_i64")]
+    pub status: Option<i64>,
+    /// Time of the last report, in the plant's local time.
+    #[serde(rename = "lastUpdateTime", default, deserialize_with = "deserialize_lenient_datetime")]
+    pub last_update_time: Option<NaiveDateTime>,
+    /// Ambient temperature (°C).
+    #[serde(rename = "envTemp", alias = "temperature", default, deserialize_with = "deserialize_lenient_f64")]
+    pub temperature: Option<f64>,
+    /// Relative humidity (%).
+    #[serde(rename = "envHumidity", alias = "humidity", default, deserialize_with = "deserialize_lenient_f64")]
+    pub humidity: Option<f64>,
+    /// Solar irradiance (W/m²).
+    #[serde(rename = "radiant", alias = "irradiance", default, deserialize_with = "deserialize_lenient_f64")]
+    pub irradiance: Option<f64>,
+    /// Wind speed (m/s).
+    #[serde(rename = "windSpeed", default, deserialize_with = "deserialize_lenient_f64")]
+    pub wind_speed: Option<f64>,
+    /// Temperature of the reference panel (°C).
+    #[serde(rename = "panelTemp", default, deserialize_with = "deserialize_lenient_f64")]
+    pub panel_temperature: Option<f64>,
+}
+
+impl EnvSensor {
+    // Entries without a serial are skipped
+    pub(crate) fn list_from_response(json_response: &serde_json::Value) -> Vec<Self> {
+        json_response
+            .get("obj")
+            .and_then(|obj| obj.get("datas"))
+            .and_then(|datas| datas.as_array())
+            .map(|datas| {
+                datas
+                    .iter()
+                    .filter_map(|sensor| Self::deserialize(sensor).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Weather {
+    /// Parses the weather object, which wraps a HeWeather report.
+    pub(crate) fn from_value(obj: &serde_json::Value) -> Option<Self> {
+        let report = obj.get("data")?.get("HeWeather6")?.get(0)?;
+        let now = report.get("now");
+        let text = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(str::to_owned);
+
+        let forecast = report
+            .get("daily_forecast")
+            .and_then(|days| days.as_array())
+            .map(|days| {
+                days.iter()
+                    .filter_map(|day| {
+                        Some(ForecastEntry {
+                            date: NaiveDate::parse_from_str(day.get("date")?.as_str()?, "%Y-%m-%d").ok()?,
+                            condition: text(day.get("cond_txt_d")),
+                            temp_high: day.get("tmp_max").and_then(value_as_f64),
+                            temp_low: day.get("tmp_min").and_then(value_as_f64),
+                            irradiance: day
+                                .get("irradiance")
+                                .or_else(|| day.get("radiation"))
+                                .and_then(value_as_f64),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            temperature: now.and_then(|now| now.get("tmp")).and_then(value_as_f64),
+            condition: text(now.and_then(|now| now.get("cond_txt"))),
+            humidity: now.and_then(|now| now.get("hum")).and_then(value_as_f64),
+            forecast,
+        })
+    }
+}
+
+impl Growatt {
+    /// Fetches the current weather and short forecast for the plant's location.
+    pub async fn get_plant_weather(&mut self, plant_id: &str) -> Result<Weather> {
+        let form = [("plantId", plant_id)];
+        let json_response = self.post_form("/index/getWeatherByPlantId", &form).await?;
+        check_plant_access(&json_response, plant_id)?;
+        let obj = response_obj(json_response)?;
+
+        let mut weather = Weather::from_value(&obj).ok_or_else(|| {
+            GrowattError::InvalidResponse("Invalid weather response structure".to_string())
+        })?;
+        if let Some(units) = self.si_conversion() {
+            weather.to_si(&units);
+        }
+        Ok(weather)
+    }
+
+    /// Lists the plant's environmental sensors with their latest readings,
+    /// parsed from [`Growatt::get_weather`].
+    ///
+    /// Most plants have no sensor installed, which gives an empty list rather
+    /// than an error.
+    pub async fn get_env_sensors(&mut self, plant_id: &str) -> Result<Vec<EnvSensor>> {
+        let json_response = self.get_weather(plant_id).await?;
+        Ok(EnvSensor::list_from_response(&json_response))
+    }
+
+    /// Fetches the daily weather forecast for the plant's location, e.g. to
+    /// estimate tomorrow's generation.
+    ///
+    /// This is the forecast part of [`Growatt::get_plant_weather`].
+    pub async fn get_weather_forecast(&mut self, plant_id: &str) -> Result<Vec<ForecastEntry>> {
+        Ok(self.get_plant_weather(plant_id).await?.forecast)
+    }
+}